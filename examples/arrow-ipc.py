@@ -0,0 +1,39 @@
+# This example writes receipts to Arrow IPC files and memory maps them with pyarrow,
+# then gets a single page as raw IPC bytes.
+import hyperfuel
+from hyperfuel import ReceiptField
+import asyncio
+import pyarrow as pa
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.AMOUNT,
+            ]
+        )
+    )
+
+    summary = await client.create_arrow_folder(query, "data-arrow")
+    print(summary)
+
+    with pa.memory_map("data-arrow/receipt.arrow") as source:
+        receipts = pa.ipc.open_file(source).read_all()
+        print(receipts.num_rows)
+
+    res = await client.get_arrow_ipc(query)
+    receipts = pa.ipc.open_stream(res.data.receipts).read_all()
+    print(receipts.schema)
+
+asyncio.run(main())
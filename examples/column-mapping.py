@@ -0,0 +1,37 @@
+# This example fetches receipts as pyarrow data and casts some of the columns
+# on the rust side before they are handed over to pyarrow.
+import hyperfuel
+from hyperfuel import ReceiptField, BlockField, ColumnMapping, DataType
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            block=[BlockField.HEIGHT, BlockField.TIME],
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.AMOUNT,
+            ]
+        )
+    )
+
+    column_mapping = ColumnMapping(
+        block={BlockField.TIME: DataType.TIMESTAMP},
+        receipt={ReceiptField.AMOUNT: DataType.FLOAT64},
+    )
+
+    res = await client.get_arrow_data(query, column_mapping)
+    print(res.data.blocks.schema)
+    print(res.data.receipts.schema)
+
+asyncio.run(main())
@@ -0,0 +1,35 @@
+# This example writes the transfers out of a contract to CSV and NDJSON files.
+import hyperfuel
+from hyperfuel import ReceiptField, ReceiptType, TextConfig
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"],
+                receipt_type=[ReceiptType.TRANSFER_OUT]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.TO_ADDRESS,
+                ReceiptField.ASSET_ID,
+                ReceiptField.AMOUNT,
+            ]
+        )
+    )
+
+    summary = await client.create_csv_folder(query, "data-csv")
+    print(summary)
+
+    summary = await client.create_ndjson_folder(query, "data-ndjson", TextConfig(hex_numbers=True))
+    print(summary)
+
+asyncio.run(main())
@@ -0,0 +1,19 @@
+# This example decodes the logs of a contract using its Sway JSON ABI.
+import hyperfuel
+import asyncio
+
+CONTRACT = "0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"
+
+async def main():
+    with open("abi.json") as f:
+        abi = f.read()
+
+    client = hyperfuel.HyperfuelClient(abis={CONTRACT: abi})
+
+    query = hyperfuel.LogQuery(from_block=0, to_block=1300000, contracts=[CONTRACT], receipt_type=[hyperfuel.ReceiptType.LOG_DATA])
+
+    async for page in client.stream_logs(query):
+        for log in page.data:
+            print(str(log.rb) + ": " + str(log.decoded))
+
+asyncio.run(main())
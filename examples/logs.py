@@ -0,0 +1,25 @@
+# This example fetches the LogData receipts with a given log id (rb) emitted by a contract,
+# following the pagination until the end of the block range.
+import hyperfuel
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.LogQuery(
+        from_block=0,
+        to_block=1300000,
+        contracts=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"],
+        rb=[1515152261580153489],
+        receipt_type=[hyperfuel.ReceiptType.LOG_DATA],
+    )
+
+    while True:
+        res = await client.get_logs(query)
+        print("next_block: " + str(res.next_block) + ", logs: " + str(len(res.data)))
+
+        if res.next_block >= query.to_block:
+            break
+        query.from_block = res.next_block
+
+asyncio.run(main())
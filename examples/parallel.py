@@ -0,0 +1,41 @@
+# This example backfills receipts of a contract by fetching chunks of the block range in parallel.
+import hyperfuel
+from hyperfuel import ReceiptField, BlockField, ParquetConfig
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            block=[BlockField.HEIGHT, BlockField.TIME],
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.AMOUNT,
+            ]
+        )
+    )
+
+    # merged into a single response with pyarrow tables
+    res = await client.parallel_collect(query, num_workers=8, chunk_blocks=50000)
+    print(f"receipts: {res.data.receipts.num_rows}, next_block: {res.next_block}")
+
+    # or written to a parquet folder in block order
+    summary = await client.parallel_collect(
+        query,
+        num_workers=8,
+        chunk_blocks=50000,
+        path="data",
+        config=ParquetConfig(partition_blocks=500000),
+    )
+    print(summary)
+
+asyncio.run(main())
@@ -0,0 +1,36 @@
+# This example exports receipts of a contract to parquet files. The export reports its progress
+# and can be interrupted, running it again continues from the last committed block.
+import hyperfuel
+from hyperfuel import ReceiptField, BlockField, ParquetConfig
+import asyncio
+
+def on_progress(progress):
+    print(f"next_block: {progress.next_block}, blocks: {progress.blocks_processed}, receipts: {progress.rows.receipt}")
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            block=[BlockField.HEIGHT, BlockField.TIME],
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.AMOUNT,
+            ]
+        )
+    )
+
+    config = ParquetConfig(checkpoint_interval_blocks=200000)
+
+    summary = await client.create_parquet_folder(query, "data", config=config, progress=on_progress)
+    print(summary)
+
+asyncio.run(main())
@@ -0,0 +1,33 @@
+# This example fetches receipts of a contract as polars DataFrames.
+import hyperfuel
+from hyperfuel import ReceiptField
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.RECEIPT_TYPE,
+            ]
+        )
+    )
+
+    res = await client.get_polars_data(query)
+    print(res.data.receipts)
+
+    async for page in client.stream_polars(query):
+        if page.data.receipts is not None:
+            print(page.data.receipts.group_by("receipt_type").len())
+
+asyncio.run(main())
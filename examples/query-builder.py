@@ -0,0 +1,28 @@
+# This example builds a query with the QueryBuilder instead of nesting the dataclasses.
+import hyperfuel
+from hyperfuel import QueryBuilder, ReceiptSelection, ReceiptField, BlockField, errors
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = (
+        QueryBuilder()
+        .from_block(0)
+        .to_block(1300000)
+        .receipts(ReceiptSelection(root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]))
+        .select_block_fields([BlockField.HEIGHT, BlockField.TIME])
+        .select_receipt_fields([ReceiptField.TX_ID, ReceiptField.AMOUNT])
+        .build()
+    )
+
+    res = await client.get_data(query)
+    print(f"receipts: {len(res.data.receipts)}")
+
+    # typos are caught when the step is applied
+    try:
+        QueryBuilder().select_receipt_fields(["amout"])
+    except errors.QueryValidationError as e:
+        print(f"invalid field {e.field}")
+
+asyncio.run(main())
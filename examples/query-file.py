@@ -0,0 +1,28 @@
+# This example reads a query from a file and writes it back as JSON.
+import hyperfuel
+from hyperfuel import Query
+import asyncio
+
+QUERY_YAML = """
+from_block: 0
+to_block: 1300000
+receipts:
+  - root_contract_id:
+      - "0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"
+field_selection:
+  receipt: [tx_id, amount]
+"""
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    # Query.from_file("query.yaml") picks the format by extension
+    query = Query.from_yaml(QUERY_YAML)
+
+    res = await client.get_data(query)
+    print(f"receipts: {len(res.data.receipts)}")
+
+    # the JSON uses the same layout as the server side query type
+    print(query.to_json(pretty=True))
+
+asyncio.run(main())
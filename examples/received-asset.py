@@ -22,8 +22,8 @@ async def main():
             hyperfuel.ReceiptSelection(
                 # only return receipts that have `asset_id` field = this asset_id...
                 asset_id=[asset_id],
-                # ...AND that are type 8 (TransferOut)
-                receipt_type=[8] 
+                # ...AND that are TransferOut receipts
+                receipt_type=[hyperfuel.ReceiptType.TRANSFER_OUT]
             ),
         ],
         # what data we want returned from the receipts we queried for
@@ -0,0 +1,24 @@
+# This example runs a few queries and prints the request metrics of the client.
+import hyperfuel
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    height = await client.get_height()
+
+    query = hyperfuel.Query(
+        from_block=max(height - 1000, 0),
+        field_selection=hyperfuel.FieldSelection(block=[hyperfuel.BlockField.HEIGHT])
+    )
+    await client.get_arrow_data(query)
+    await client.get_data(query)
+
+    stats = client.stats()
+    for method, s in stats.methods.items():
+        print(f"{method}: {s.requests} requests, {s.retries} retries, {s.arrow_bytes} bytes of arrow data")
+
+    # e.g. to serve from a metrics endpoint
+    print(stats.to_prometheus())
+
+asyncio.run(main())
@@ -0,0 +1,32 @@
+# This example streams all receipts of a contract, page by page, fetching
+# several block ranges in parallel.
+import hyperfuel
+from hyperfuel import ReceiptField
+import asyncio
+
+async def main():
+    client = hyperfuel.HyperfuelClient()
+
+    query = hyperfuel.Query(
+        from_block=0,
+        to_block=1300000,
+        receipts=[
+            hyperfuel.ReceiptSelection(
+                root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+            )
+        ],
+        field_selection=hyperfuel.FieldSelection(
+            receipt=[
+                ReceiptField.BLOCK_HEIGHT,
+                ReceiptField.TX_ID,
+                ReceiptField.RECEIPT_TYPE,
+            ]
+        )
+    )
+
+    config = hyperfuel.StreamConfig(concurrency=4, batch_size=50000)
+
+    async for page in client.stream(query, config):
+        print("next_block: " + str(page.next_block) + ", receipts: " + str(len(page.data.receipts)))
+
+asyncio.run(main())
@@ -0,0 +1,30 @@
+# This example uses the blocking client, no asyncio event loop is needed.
+import hyperfuel
+from hyperfuel import ReceiptField
+
+client = hyperfuel.HyperfuelClientSync()
+
+print("height: " + str(client.get_height()))
+
+query = hyperfuel.Query(
+    from_block=0,
+    to_block=1300000,
+    receipts=[
+        hyperfuel.ReceiptSelection(
+            root_contract_id=["0x4a2ce054e3e94155f7092f7365b212f7f45105b74819c623744ebcc5d065c6ac"]
+        )
+    ],
+    field_selection=hyperfuel.FieldSelection(
+        receipt=[
+            ReceiptField.BLOCK_HEIGHT,
+            ReceiptField.TX_ID,
+            ReceiptField.RECEIPT_TYPE,
+        ]
+    )
+)
+
+res = client.get_selected_data(query)
+print("next_block: " + str(res.next_block) + ", receipts: " + str(len(res.data.receipts)))
+
+for page in client.stream(query, hyperfuel.StreamConfig(batch_size=50000)):
+    print("next_block: " + str(page.next_block) + ", receipts: " + str(len(page.data.receipts)))
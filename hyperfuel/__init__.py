@@ -1,8 +1,19 @@
 from .hyperfuel import HyperfuelClient as _HyperfuelClient
-from typing import Optional
+from .hyperfuel import HyperfuelClientSync as _HyperfuelClientSync
+from .hyperfuel import QueryBuilder as _QueryBuilder
+from .hyperfuel import query_to_json as _query_to_json, query_from_text as _query_from_text
+from .hyperfuel import ReceiptType, InputType, OutputType, TransactionType, TransactionStatus
+from . import errors
+import logging
+import os
+from typing import Callable, Optional
 from dataclasses import dataclass, asdict
 from strenum import StrEnum
 
+# Log records of the rust code are forwarded to the 'hyperfuel' logger and its children
+# (e.g. 'hyperfuel.retry', 'hyperfuel.hyperfuel_client.client') with the matching levels,
+# trace records use level 5. Nothing is printed unless the application configures logging.
+logging.getLogger("hyperfuel").addHandler(logging.NullHandler())
 
 class DataType(StrEnum):
     UINT64 = 'uint64'
@@ -11,6 +22,15 @@ class DataType(StrEnum):
     INT32 = 'int32'
     FLOAT32 = 'float32'
     FLOAT64 = 'float64'
+    # Timestamp with seconds resolution in UTC, for the 'time' columns
+    TIMESTAMP = 'timestamp'
+
+class Compression(StrEnum):
+    LZ4_RAW = 'lz4_raw'
+    LZ4 = 'lz4'
+    ZSTD = 'zstd'
+    GZIP = 'gzip'
+    UNCOMPRESSED = 'uncompressed'
 
 class BlockField(StrEnum):
     ID = 'id'
@@ -144,12 +164,14 @@ class OutputField(StrEnum):
     CONTRACT = 'contract'
 
 
+# The type fields of the selections take the enums exported from the rust module
+# (ReceiptType, InputType, OutputType, TransactionType, TransactionStatus) or their numeric values.
 @dataclass
 class ReceiptSelection:
     root_contract_id: Optional[list[str]] = None
     to_address: Optional[list[str]] = None
     asset_id: Optional[list[str]] = None
-    receipt_type: Optional[list[str]] = None
+    receipt_type: Optional[list[ReceiptType]] = None
     sender: Optional[list[str]] = None
     recipient: Optional[list[str]] = None
     contract_id: Optional[list[str]] = None
@@ -157,8 +179,8 @@ class ReceiptSelection:
     rb: Optional[list[str]] = None
     rc: Optional[list[str]] = None
     rd: Optional[list[str]] = None
-    tx_status: Optional[list[int]] = None
-    tx_type: Optional[list[int]] = None
+    tx_status: Optional[list[TransactionStatus]] = None
+    tx_type: Optional[list[TransactionType]] = None
 
 
 @dataclass
@@ -168,9 +190,9 @@ class InputSelection:
     contract: Optional[list[str]] = None
     sender: Optional[list[str]] = None
     recipient: Optional[list[str]] = None
-    input_type: Optional[list[str]] = None
-    tx_status: Optional[list[int]] = None
-    tx_type: Optional[list[int]] = None
+    input_type: Optional[list[InputType]] = None
+    tx_status: Optional[list[TransactionStatus]] = None
+    tx_type: Optional[list[TransactionType]] = None
 
 
 @dataclass
@@ -178,9 +200,9 @@ class OutputSelection:
     to: Optional[list[str]] = None
     asset_id: Optional[list[str]] = None
     contract: Optional[list[str]] = None
-    output_type: Optional[list[str]] = None
-    tx_status: Optional[list[int]] = None
-    tx_type: Optional[list[int]] = None
+    output_type: Optional[list[OutputType]] = None
+    tx_status: Optional[list[TransactionStatus]] = None
+    tx_type: Optional[list[TransactionType]] = None
 
 
 @dataclass
@@ -195,6 +217,8 @@ class FieldSelection:
 class Query:
     from_block: int
     field_selection: FieldSelection
+    # Exclusive end of the block range [from_block, to_block). Without it, streams, exports and
+    # parallel_collect run up to and including the current height of the source.
     to_block: Optional[int] = None
     include_all_blocks: Optional[bool] = None
     receipts: Optional[list[ReceiptSelection]] = None
@@ -203,15 +227,197 @@ class Query:
     max_num_blocks: Optional[int] = None
     max_num_transactions: Optional[int] = None
 
+    # Serialize the query to the JSON format of hyperfuel_net_types::Query, which Rust
+    # services using the same net types can deserialize directly.
+    def to_json(self, pretty: bool = False) -> str:
+        return _query_to_json(asdict(self), pretty)
+
+    # Read a query from JSON, e.g. written by to_json or by a Rust service.
+    # Invalid values raise errors.QueryValidationError.
+    @staticmethod
+    def from_json(data: str) -> 'Query':
+        return dict_to_query(_query_from_text(data, "json"))
+
+    # Read a query from YAML with the same structure as the JSON format.
+    @staticmethod
+    def from_yaml(data: str) -> 'Query':
+        return dict_to_query(_query_from_text(data, "yaml"))
+
+    # Read a query from TOML with the same structure as the JSON format.
+    @staticmethod
+    def from_toml(data: str) -> 'Query':
+        return dict_to_query(_query_from_text(data, "toml"))
+
+    # Read a query definition from a '.json', '.yaml', '.yml' or '.toml' file.
+    @staticmethod
+    def from_file(path: str) -> 'Query':
+        formats = {".json": "json", ".yaml": "yaml", ".yml": "yaml", ".toml": "toml"}
+        extension = os.path.splitext(path)[1].lower()
+        if extension not in formats:
+            raise ValueError(f"unknown query file extension '{extension}', expected one of {', '.join(formats)}")
+        with open(path) as f:
+            return dict_to_query(_query_from_text(f.read(), formats[extension]))
+
+@dataclass
+class LogQuery:
+    # The block to start the query from
+    from_block: int
+    # Contracts that emitted the logs (root_contract_id of the receipt)
+    contracts: list[str]
+    # The block to end the query at, exclusive. If not specified, the query will go until the
+    # end of data.
+    to_block: Optional[int] = None
+    # Log ids to select (the rb register of the receipt), all logs are returned if not specified
+    rb: Optional[list[int]] = None
+    # Receipt types to select, ReceiptType.LOG and/or ReceiptType.LOG_DATA. Both are returned if not specified
+    receipt_type: Optional[list[ReceiptType]] = None
+    # Also return logs of failed transactions, default is False
+    include_failed: Optional[bool] = None
+
+# Casts columns of the arrow data to other types, keys are column names of the table
+# and values are the DataType the column is cast to.
+# For example ColumnMapping(receipt={'amount': DataType.FLOAT64}, block={'time': DataType.TIMESTAMP})
+@dataclass
+class ColumnMapping:
+    block: Optional[dict[str, DataType]] = None
+    transaction: Optional[dict[str, DataType]] = None
+    receipt: Optional[dict[str, DataType]] = None
+    input: Optional[dict[str, DataType]] = None
+    output: Optional[dict[str, DataType]] = None
+
+@dataclass
+class StreamConfig:
+    # Number of block ranges to fetch in parallel, default is 4
+    concurrency: Optional[int] = None
+    # Number of blocks in each block range that is fetched in parallel, default is 100_000
+    batch_size: Optional[int] = None
+    # Number of pages that can be buffered before fetching is paused until the
+    # consumer catches up, default is 16
+    buffer_size: Optional[int] = None
+    # Interval in milliseconds for polling the height of the source once the
+    # head of the chain is reached in tail mode, default is 1 second (1_000ms)
+    poll_interval_millis: Optional[int] = None
+
+@dataclass
+class ParquetConfig:
+    # Number of blocks after which the files that are being written are finished and
+    # committed to the checkpoint, the following blocks are written to a new part
+    # ('receipt.1.parquet', 'receipt.2.parquet', ...).
+    # An interrupted export continues from the last commit. Default is 1_000_000 blocks.
+    checkpoint_interval_blocks: Optional[int] = None
+    # Continue from the checkpoint of a previous run of the same query in the output folder,
+    # default is True. If False, the previous output is replaced.
+    resume: Optional[bool] = None
+    # Write Hive style partitions of this many blocks per table instead of a file per table,
+    # e.g. 'receipts/block_bucket=1000000/part-0.parquet', so the folder can be read by DuckDB or Spark.
+    # Default is to not partition the output.
+    partition_blocks: Optional[int] = None
+    # Number of rows of a table after which its file is finished and the following rows are written
+    # to a new part. Files can exceed it by the rows of a single response. Default is no limit.
+    max_rows_per_file: Optional[int] = None
+    # Compression codec of the parquet files, default is Compression.LZ4_RAW
+    compression: Optional[Compression] = None
+
+class IpcFormat(StrEnum):
+    # Arrow IPC file format (Feather v2), can be memory mapped
+    FILE = 'file'
+    # Arrow IPC streaming format
+    STREAM = 'stream'
+
+@dataclass
+class IpcConfig:
+    # Format of the written files, IpcFormat.FILE writes '<table>.arrow' files and
+    # IpcFormat.STREAM writes '<table>.arrows' files. Default is IpcFormat.FILE.
+    format: Optional[IpcFormat] = None
+    # Same as in ParquetConfig
+    checkpoint_interval_blocks: Optional[int] = None
+    # Same as in ParquetConfig
+    resume: Optional[bool] = None
+
+@dataclass
+class TextConfig:
+    # Write amounts as 0x prefixed hex instead of decimal, default is False. Heights, indexes and
+    # enum values like receipt_type or status stay decimal. Binary fields like ids are always hex encoded.
+    hex_numbers: Optional[bool] = None
+    # Same as in ParquetConfig
+    checkpoint_interval_blocks: Optional[int] = None
+    # Same as in ParquetConfig
+    resume: Optional[bool] = None
+
 class HyperfuelClient:
     # Create a new client with given config
-    def __init__(self, url="https://fuel-testnet.hypersync.xyz", bearer_token=None, http_req_timeout_millis=None):
+    #
+    # Failed requests are retried with an exponential backoff with jitter, starting at
+    # 'retry_base_ms' and capped at 'retry_ceiling_ms', for at most 'max_num_retries' times.
+    # 'retry_on' lists the error classes that are retried, one of "timeout", "connection",
+    # "rate_limit", "auth", "server", "http", "decode" or "other".
+    #
+    # 'max_concurrent_requests' caps the number of requests in flight and 'requests_per_second' the
+    # average request rate, allowing bursts of up to a second worth of requests. Both limits are shared
+    # by all methods of the client, including its streams, parallel fetches and retries.
+    #
+    # 'abis' maps contract ids to the Sway JSON ABI (as a string) of the contract. LogData receipts
+    # emitted by these contracts are decoded and the result is put in the 'decoded' field of the logs
+    # returned by get_logs, stream_logs, tail_logs and preset_query_get_logs. Both encoding v0 and v1
+    # payloads are decoded, following the encoding version declared by the ABI.
+    # For ABIs using encoding v0, CALL receipts to these contracts in get_data, get_selected_data,
+    # stream and tail results get a 'decoded_call' with the called function and, if they fit into
+    # param2, its arguments. Calls can't be resolved with ABIs using encoding v1 (the default of
+    # specVersion ABIs) since the receipt doesn't hold the function selector, a warning is logged when
+    # such an ABI is registered and calls to the contract are left undecoded.
+    #
+    # If 'native_types' is set, ids, addresses and other binary fields of the typed responses
+    # (get_data, get_selected_data, stream and tail) and of the log responses (get_logs,
+    # preset_query_get_logs, stream_logs and tail_logs) are returned as bytes and quantities like
+    # 'transactions_count' as int, instead of hex strings. This includes b256 and bytes values in the
+    # 'decoded' logs and the 'decoded_call' arguments.
+    #
+    # If 'cache_dir' is set, arrow responses (get_arrow_data, get_polars_data, get_arrow_ipc, the arrow
+    # streams and the parquet and arrow folder exports) are cached in that folder. Only pages that end
+    # below the archive height of the source are stored, identical queries are then served from disk.
+    # Typed data, logs and the height are not cached. A cached page reports the archive height it was
+    # fetched with. There is no size limit or eviction, remove the folder to clear the cache.
+    def __init__(
+        self,
+        url="https://fuel-testnet.hypersync.xyz",
+        bearer_token=None,
+        http_req_timeout_millis=None,
+        max_num_retries: Optional[int] = None,
+        retry_base_ms: Optional[int] = None,
+        retry_ceiling_ms: Optional[int] = None,
+        retry_on: Optional[list[str]] = None,
+        max_concurrent_requests: Optional[int] = None,
+        requests_per_second: Optional[float] = None,
+        abis: Optional[dict[str, str]] = None,
+        native_types: Optional[bool] = None,
+        cache_dir: Optional[str] = None,
+    ):
         self.inner = _HyperfuelClient({
             "url": url,
             "bearer_token": bearer_token,
-            "http_req_timeout_millis": http_req_timeout_millis
+            "http_req_timeout_millis": http_req_timeout_millis,
+            "max_num_retries": max_num_retries,
+            "retry_base_ms": retry_base_ms,
+            "retry_ceiling_ms": retry_ceiling_ms,
+            "retry_on": retry_on,
+            "max_concurrent_requests": max_concurrent_requests,
+            "requests_per_second": requests_per_second,
+            "abis": abis,
+            "native_types": native_types,
+            "cache_dir": cache_dir,
         })
 
+    # Get the request metrics of the client.
+    #
+    # Returns a ClientStats with a MethodStats per request method ("get_height", "get_data",
+    # "get_selected_data", "get_arrow_data" and "preset_query_get_logs") in its 'methods' dict:
+    # request, error and retry counts, an estimate of the in-memory size of the arrow data of
+    # get_arrow_data responses ('arrow_bytes', not the bytes downloaded and zero for the typed data
+    # and log methods), the summed execution time reported by the server and a latency histogram. Responses served from the cache are not counted.
+    # 'client.stats().to_prometheus()' renders the metrics in the Prometheus text format.
+    def stats(self) -> any:
+        return self.inner.stats()
+
 # Create a parquet file by executing a query.
     #
     # If the query can't be finished in a single request, this function will
@@ -220,46 +426,140 @@ class HyperfuelClient:
     # the server.
     #
     # Path should point to a folder that will contain the parquet files in the end.
-    async def create_parquet_folder(self, query: Query, path: str) -> None:
-        return await self.inner.create_parquet_folder(asdict(query), path)
+    # Columns are cast to the types in 'column_mapping' before they are written.
+    #
+    # 'progress' is called with the progress of the export after every page, it has the 'next_block',
+    # the number of 'blocks_processed' and the 'rows' written per table. Raising an exception from it
+    # aborts the export.
+    # Written parts are committed to a checkpoint file in the folder, every 'checkpoint_interval_blocks'
+    # blocks of the config and at the end, so an interrupted export of the same query continues from the last
+    # committed block when it is run again.
+    # Running it again with a higher 'to_block' appends the following blocks to the folder.
+    # Returns a summary with the 'rows' and 'bytes' written per table and the 'elapsed_millis' of the run.
+    async def create_parquet_folder(
+        self,
+        query: Query,
+        path: str,
+        column_mapping: Optional[ColumnMapping] = None,
+        config: Optional[ParquetConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return await self.inner.create_parquet_folder(asdict(query), path, asdict(column_mapping) if column_mapping is not None else None, asdict(config) if config is not None else None, progress)
+
+    # Run a query over its whole block range with several workers in parallel.
+    #
+    # The range is split into chunks of 'chunk_blocks' blocks and up to 'num_workers' chunks are
+    # fetched at the same time, each with its own pagination loop on the shared connection.
+    # The query runs to its (exclusive) to_block, or up to and including the height of the source if it has none.
+    #
+    # Without a 'path', the pages are merged in block order into a single query response with
+    # pyarrow tables, so the whole range has to fit in memory.
+    # With a 'path', the pages are written in block order to a parquet folder like in create_parquet_folder,
+    # using 'config' and 'progress' the same way, and the ExportSummary is returned.
+    async def parallel_collect(
+        self,
+        query: Query,
+        num_workers: int = 4,
+        chunk_blocks: int = 100_000,
+        column_mapping: Optional[ColumnMapping] = None,
+        path: Optional[str] = None,
+        config: Optional[ParquetConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return await self.inner.parallel_collect(asdict(query), num_workers, chunk_blocks, asdict(column_mapping) if column_mapping is not None else None, path, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of Arrow IPC files by executing a query, one file per table.
+    #
+    # Pages are streamed to the files as they come from the server and committed to a checkpoint,
+    # like in create_parquet_folder, so an interrupted export is resumed by running it again.
+    # The IPC file format (Feather v2) is used by default so the files can be memory mapped by other
+    # processes, e.g. with 'pyarrow.memory_map' and 'pyarrow.ipc.open_file'.
+    # Columns are cast to the types in 'column_mapping' before they are written.
+    # 'progress' and the returned summary are the same as in create_parquet_folder.
+    async def create_arrow_folder(
+        self,
+        query: Query,
+        path: str,
+        column_mapping: Optional[ColumnMapping] = None,
+        config: Optional[IpcConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return await self.inner.create_arrow_folder(asdict(query), path, asdict(column_mapping) if column_mapping is not None else None, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of CSV files by executing a query, one '<table>.csv' file per table.
+    #
+    # Pages are streamed to the files as they come from the server and committed to a checkpoint,
+    # like in create_parquet_folder, so an interrupted export is resumed by running it again.
+    # The columns are the selected fields, named like the fields of the typed data.
+    # Binary fields are hex encoded and integers are written as decimal, 'hex_numbers' in the config
+    # writes amounts as hex.
+    # 'progress' and the returned summary are the same as in create_parquet_folder.
+    async def create_csv_folder(
+        self,
+        query: Query,
+        path: str,
+        config: Optional[TextConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return await self.inner.create_csv_folder(asdict(query), path, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of newline delimited JSON files by executing a query, one '<table>.ndjson' file per table.
+    #
+    # Same as create_csv_folder but every row is written as a JSON object on its own line.
+    async def create_ndjson_folder(
+        self,
+        query: Query,
+        path: str,
+        config: Optional[TextConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return await self.inner.create_ndjson_folder(asdict(query), path, asdict(config) if config is not None else None, progress)
 
     # Get the height of the source hypersync instance
+    # On an error from the source hypersync instance, the request is retried
+    # according to the retry settings of the client.
     async def get_height(self) -> int:    
         return await self.inner.get_height()
         
     # Get the height of the source hypersync instance
-    # Internally calls get_height.
-    # On an error from the source hypersync instance, sleeps for
-    # 1 second (increasing by 1 each failure up to max of 5 seconds)
-    # and retries query until success.
+    #
+    # Deprecated, emits a DeprecationWarning. Use get_height instead, it retries failed
+    # requests according to the retry settings of the client.
     async def get_height_with_retry(self) -> int:
         return await self.inner.get_height_with_retry()
     
     # Send a query request to the source hypersync instance.
     #
     # Returns a query response which contains pyarrow data.
+    # Columns are cast to the types in 'column_mapping' before they are handed over to pyarrow.
     #
     # NOTE: this query returns loads all transactions that your match your receipt, input, or output selections
     # and applies the field selection to all these loaded transactions.  So your query will return the data you
     # want plus additional data from the loaded transactions.  This functionality is in case you want to associate
     # receipts, inputs, or outputs with eachother.
-    async def get_arrow_data(self, query: Query) -> any:
-        return await self.inner.get_arrow_data(asdict(query))
+    async def get_arrow_data(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return await self.inner.get_arrow_data(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
     
     # Send a query request to the source hypersync instance.
-    # On an error from the source hypersync instance, sleeps for
-    # 1 second (increasing by 1 each failure up to max of 5 seconds)
-    # and retries query until success.
     #
-    # Returns a query response which contains pyarrow data.
+    # Deprecated, emits a DeprecationWarning. Use get_arrow_data instead, it retries failed
+    # requests according to the retry settings of the client.
+    async def get_arrow_data_with_retry(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return await self.inner.get_arrow_data_with_retry(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
     #
-    # NOTE: this query returns loads all transactions that your match your receipt, input, or output selections
-    # and applies the field selection to all these loaded transactions.  So your query will return the data you
-    # want plus additional data from the loaded transactions.  This functionality is in case you want to associate
-    # receipts, inputs, or outputs with eachother.
-    # Format can be ArrowIpc.
-    async def get_arrow_data_with_retry(self, query: Query) -> any:
-        return await self.inner.get_arrow_data_with_retry(asdict(query))
+    # Same as get_arrow_data but every table is returned as bytes in the Arrow IPC streaming format,
+    # e.g. to be read with 'pyarrow.ipc.open_stream' or passed on to other processes.
+    async def get_arrow_ipc(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return await self.inner.get_arrow_ipc(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Same as get_arrow_data but returns polars DataFrames, the arrow data is handed over
+    # to polars directly without going through pyarrow. Requires polars to be installed.
+    async def get_polars_data(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return await self.inner.get_polars_data(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
 
     # Send a query request to the source hypersync instance.
     #
@@ -292,22 +592,278 @@ class HyperfuelClient:
     async def preset_query_get_logs(self, emitting_contracts: list[str], from_block: int, to_block: Optional[int]) -> any:
         return await self.inner.preset_query_get_logs(emitting_contracts, from_block, to_block)
 
+    # Send a log query request to the source hypersync instance.
+    #
+    # Returns the Log and/or LogData receipts emitted by the selected contracts, optionally
+    # filtered by log id (rb). Logs of failed transactions are only returned if 'include_failed' is set.
+    # The response is paginated like any other query, continue from its next_block if it didn't
+    # reach the to_block of the query.
+    async def get_logs(self, query: LogQuery) -> any:
+        return await self.inner.get_logs(asdict(query))
+
+    # Stream the results of a query, page by page.
+    #
+    # Splits the block range of the query into batches and runs several of them in parallel,
+    # the pages are yielded in block order. Returns an async iterator that yields typed pages.
+    # Fetching is paused when the consumer falls behind and stops when the iterator is closed or dropped.
+    #
+    # If no 'to_block' is specified, the stream will run up to and including the current height of the source.
+    def stream(self, query: Query, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.stream(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query, page by page.
+    #
+    # Same as stream but yields pages that contain pyarrow data.
+    def stream_arrow(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.stream_arrow(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a query, page by page.
+    #
+    # Same as stream but yields pages that contain polars DataFrames.
+    def stream_polars(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.stream_polars(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a log query, page by page.
+    #
+    # Same as stream but yields log response pages.
+    def stream_logs(self, query: LogQuery, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.stream_logs(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query and keep following the head of the chain.
+    #
+    # Works like stream but doesn't stop once it catches up to the height of the source.
+    # It polls the height every 'poll_interval_millis' and yields new pages as blocks land.
+    # If a 'to_block' is specified, the stream stops once it reaches it, otherwise it runs until
+    # the iterator is closed or dropped.
+    def tail(self, query: Query, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.tail(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query and keep following the head of the chain.
+    #
+    # Same as tail but yields pages that contain pyarrow data.
+    def tail_arrow(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.tail_arrow(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a log query and keep following the head of the chain.
+    #
+    # Same as tail but yields log response pages.
+    def tail_logs(self, query: LogQuery, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.tail_logs(asdict(query), asdict(config) if config is not None else None)
+
+class HyperfuelClientSync:
+    # Blocking version of HyperfuelClient, takes the same config.
+    #
+    # Methods return their results directly instead of awaitables, so no asyncio event loop
+    # is needed. Requests run on the client's internal runtime and the GIL is released while
+    # waiting for them. Streams are plain iterators, use 'for page in client.stream(query)'.
+    def __init__(
+        self,
+        url="https://fuel-testnet.hypersync.xyz",
+        bearer_token=None,
+        http_req_timeout_millis=None,
+        max_num_retries: Optional[int] = None,
+        retry_base_ms: Optional[int] = None,
+        retry_ceiling_ms: Optional[int] = None,
+        retry_on: Optional[list[str]] = None,
+        max_concurrent_requests: Optional[int] = None,
+        requests_per_second: Optional[float] = None,
+        abis: Optional[dict[str, str]] = None,
+        native_types: Optional[bool] = None,
+        cache_dir: Optional[str] = None,
+    ):
+        self.inner = _HyperfuelClientSync({
+            "url": url,
+            "bearer_token": bearer_token,
+            "http_req_timeout_millis": http_req_timeout_millis,
+            "max_num_retries": max_num_retries,
+            "retry_base_ms": retry_base_ms,
+            "retry_ceiling_ms": retry_ceiling_ms,
+            "retry_on": retry_on,
+            "max_concurrent_requests": max_concurrent_requests,
+            "requests_per_second": requests_per_second,
+            "abis": abis,
+            "native_types": native_types,
+            "cache_dir": cache_dir,
+        })
+
+    # Get the request metrics of the client, see HyperfuelClient.stats.
+    def stats(self) -> any:
+        return self.inner.stats()
+
+    # Create a parquet file by executing a query, see HyperfuelClient.create_parquet_folder.
+    def create_parquet_folder(
+        self,
+        query: Query,
+        path: str,
+        column_mapping: Optional[ColumnMapping] = None,
+        config: Optional[ParquetConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return self.inner.create_parquet_folder(asdict(query), path, asdict(column_mapping) if column_mapping is not None else None, asdict(config) if config is not None else None, progress)
+
+    # Run a query over its whole block range with several workers in parallel, see HyperfuelClient.parallel_collect.
+    def parallel_collect(
+        self,
+        query: Query,
+        num_workers: int = 4,
+        chunk_blocks: int = 100_000,
+        column_mapping: Optional[ColumnMapping] = None,
+        path: Optional[str] = None,
+        config: Optional[ParquetConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return self.inner.parallel_collect(asdict(query), num_workers, chunk_blocks, asdict(column_mapping) if column_mapping is not None else None, path, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of Arrow IPC files by executing a query, see HyperfuelClient.create_arrow_folder.
+    def create_arrow_folder(
+        self,
+        query: Query,
+        path: str,
+        column_mapping: Optional[ColumnMapping] = None,
+        config: Optional[IpcConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return self.inner.create_arrow_folder(asdict(query), path, asdict(column_mapping) if column_mapping is not None else None, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of CSV files by executing a query, see HyperfuelClient.create_csv_folder.
+    def create_csv_folder(
+        self,
+        query: Query,
+        path: str,
+        config: Optional[TextConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return self.inner.create_csv_folder(asdict(query), path, asdict(config) if config is not None else None, progress)
+
+    # Create a folder of newline delimited JSON files by executing a query, see HyperfuelClient.create_ndjson_folder.
+    def create_ndjson_folder(
+        self,
+        query: Query,
+        path: str,
+        config: Optional[TextConfig] = None,
+        progress: Optional[Callable[[any], None]] = None,
+    ) -> any:
+        return self.inner.create_ndjson_folder(asdict(query), path, asdict(config) if config is not None else None, progress)
+
+    # Get the height of the source hypersync instance
+    def get_height(self) -> int:
+        return self.inner.get_height()
+
+    # Get the height of the source hypersync instance
+    #
+    # Deprecated, emits a DeprecationWarning. Use get_height instead.
+    def get_height_with_retry(self) -> int:
+        return self.inner.get_height_with_retry()
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns a query response which contains pyarrow data.
+    def get_arrow_data(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.get_arrow_data(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Deprecated, emits a DeprecationWarning. Use get_arrow_data instead.
+    def get_arrow_data_with_retry(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.get_arrow_data_with_retry(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns a query response in which every table is bytes in the Arrow IPC streaming format.
+    def get_arrow_ipc(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.get_arrow_ipc(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns a query response which contains polars DataFrames.
+    def get_polars_data(self, query: Query, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.get_polars_data(asdict(query), asdict(column_mapping) if column_mapping is not None else None)
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns a query response which contains typed data.
+    def get_data(self, query: Query) -> any:
+        return self.inner.get_data(asdict(query))
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns a query response that which contains structured data that doesn't include any inputs, outputs,
+    # and receipts that don't exactly match the query's input, outout, or receipt selection.
+    def get_selected_data(self, query: Query) -> any:
+        return self.inner.get_selected_data(asdict(query))
+
+    # Send a query request to the source hypersync instance.
+    #
+    # Returns all log and logdata receipts of logs emitted by any of the specified contracts
+    # within the block range, see HyperfuelClient.preset_query_get_logs.
+    #
+    # NOTE: this function is experimental and might be removed in future versions.
+    def preset_query_get_logs(self, emitting_contracts: list[str], from_block: int, to_block: Optional[int]) -> any:
+        return self.inner.preset_query_get_logs(emitting_contracts, from_block, to_block)
+
+    # Send a log query request to the source hypersync instance.
+    def get_logs(self, query: LogQuery) -> any:
+        return self.inner.get_logs(asdict(query))
+
+    # Stream the results of a query, page by page.
+    #
+    # Returns an iterator that yields typed pages.
+    def stream(self, query: Query, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.stream(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query, page by page.
+    #
+    # Same as stream but yields pages that contain pyarrow data.
+    def stream_arrow(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.stream_arrow(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a query, page by page.
+    #
+    # Same as stream but yields pages that contain polars DataFrames.
+    def stream_polars(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.stream_polars(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a log query, page by page.
+    #
+    # Same as stream but yields log response pages.
+    def stream_logs(self, query: LogQuery, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.stream_logs(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query and keep following the head of the chain.
+    #
+    # Returns an iterator that yields typed pages.
+    def tail(self, query: Query, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.tail(asdict(query), asdict(config) if config is not None else None)
+
+    # Stream the results of a query and keep following the head of the chain.
+    #
+    # Same as tail but yields pages that contain pyarrow data.
+    def tail_arrow(self, query: Query, config: Optional[StreamConfig] = None, column_mapping: Optional[ColumnMapping] = None) -> any:
+        return self.inner.tail_arrow(asdict(query), asdict(config) if config is not None else None, asdict(column_mapping) if column_mapping is not None else None)
+
+    # Stream the results of a log query and keep following the head of the chain.
+    #
+    # Same as tail but yields log response pages.
+    def tail_logs(self, query: LogQuery, config: Optional[StreamConfig] = None) -> any:
+        return self.inner.tail_logs(asdict(query), asdict(config) if config is not None else None)
+
 
     
 
 # helper function for converting a Query object from the rust side interpreted as a dict into a 
 # dataclass Query
 def dict_to_query(data: dict) -> Query:
-    receipts = [ReceiptSelection(**receipt) for receipt in data.get('receipts', [])] if 'receipts' in data else None
-    inputs = [InputSelection(**inpt) for inpt in data.get('inputs', [])] if 'inputs' in data else None
-    outputs = [OutputSelection(**output) for output in data.get('outputs', [])] if 'outputs' in data else None
+    receipts = [ReceiptSelection(**receipt) for receipt in data['receipts']] if data.get('receipts') is not None else None
+    inputs = [InputSelection(**inpt) for inpt in data['inputs']] if data.get('inputs') is not None else None
+    outputs = [OutputSelection(**output) for output in data['outputs']] if data.get('outputs') is not None else None
     
+    fields = data['field_selection']
     field_selection = FieldSelection(
-        block=[BlockField(block) for block in data['field_selection'].get('block', [])] if 'block' in data['field_selection'] else None,
-        transaction=[TransactionField(txn) for txn in data['field_selection'].get('transaction', [])] if 'transaction' in data['field_selection'] else None,
-        receipt=[ReceiptField(receipt) for receipt in data['field_selection'].get('receipt', [])] if 'receipt' in data['field_selection'] else None,
-        input=[InputField(inpt) for inpt in data['field_selection'].get('input', [])] if 'input' in data['field_selection'] else None,
-        output=[OutputField(output) for output in data['field_selection'].get('output', [])] if 'output' in data['field_selection'] else None,
+        block=[BlockField(block) for block in fields['block']] if fields.get('block') is not None else None,
+        transaction=[TransactionField(txn) for txn in fields['transaction']] if fields.get('transaction') is not None else None,
+        receipt=[ReceiptField(receipt) for receipt in fields['receipt']] if fields.get('receipt') is not None else None,
+        input=[InputField(inpt) for inpt in fields['input']] if fields.get('input') is not None else None,
+        output=[OutputField(output) for output in fields['output']] if fields.get('output') is not None else None,
     )
     
     return Query(
@@ -321,3 +877,85 @@ def dict_to_query(data: dict) -> Query:
         max_num_transactions=data.get('max_num_transactions'),
         field_selection=field_selection,
     )
+
+# Builds a Query step by step, e.g.
+#
+#   query = QueryBuilder().from_block(0).to_block(1300000) \
+#       .receipts(ReceiptSelection(root_contract_id=[contract])) \
+#       .select_receipt_fields([ReceiptField.TX_ID, ReceiptField.AMOUNT]) \
+#       .build()
+#
+# Every step is validated right away, an invalid value like an unknown field name or a malformed id
+# raises errors.QueryValidationError and leaves the builder as it was before the step. The block
+# range is only checked by build(), so from_block and to_block can be set in any order.
+class QueryBuilder:
+    def __init__(self):
+        self.inner = _QueryBuilder()
+
+    # The block to start the query from
+    def from_block(self, block: int) -> 'QueryBuilder':
+        self.inner.from_block(block)
+        return self
+
+    # The block to end the query at, exclusive
+    def to_block(self, block: int) -> 'QueryBuilder':
+        self.inner.to_block(block)
+        return self
+
+    # Add receipt selections, receipts matching any of the selections are returned
+    def receipts(self, *selections: ReceiptSelection) -> 'QueryBuilder':
+        self.inner.receipts([asdict(selection) for selection in selections])
+        return self
+
+    # Add input selections, inputs matching any of the selections are returned
+    def inputs(self, *selections: InputSelection) -> 'QueryBuilder':
+        self.inner.inputs([asdict(selection) for selection in selections])
+        return self
+
+    # Add output selections, outputs matching any of the selections are returned
+    def outputs(self, *selections: OutputSelection) -> 'QueryBuilder':
+        self.inner.outputs([asdict(selection) for selection in selections])
+        return self
+
+    def select_block_fields(self, fields: list[BlockField]) -> 'QueryBuilder':
+        self.inner.select_block_fields(fields)
+        return self
+
+    def select_transaction_fields(self, fields: list[TransactionField]) -> 'QueryBuilder':
+        self.inner.select_transaction_fields(fields)
+        return self
+
+    def select_receipt_fields(self, fields: list[ReceiptField]) -> 'QueryBuilder':
+        self.inner.select_receipt_fields(fields)
+        return self
+
+    def select_input_fields(self, fields: list[InputField]) -> 'QueryBuilder':
+        self.inner.select_input_fields(fields)
+        return self
+
+    def select_output_fields(self, fields: list[OutputField]) -> 'QueryBuilder':
+        self.inner.select_output_fields(fields)
+        return self
+
+    # Select every field of every table
+    def all_fields(self) -> 'QueryBuilder':
+        self.inner.all_fields()
+        return self
+
+    # Return all blocks in the range, not only the ones related to the returned data
+    def include_all_blocks(self, include: bool = True) -> 'QueryBuilder':
+        self.inner.include_all_blocks(include)
+        return self
+
+    def max_num_blocks(self, max: int) -> 'QueryBuilder':
+        self.inner.max_num_blocks(max)
+        return self
+
+    def max_num_transactions(self, max: int) -> 'QueryBuilder':
+        self.inner.max_num_transactions(max)
+        return self
+
+    # The built query, it can be passed to all client methods. Raises errors.QueryValidationError if
+    # to_block isn't greater than from_block.
+    def build(self) -> Query:
+        return dict_to_query(self.inner.build())
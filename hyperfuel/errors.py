@@ -0,0 +1,35 @@
+# Exceptions raised by the hyperfuel client.
+#
+# Every exception has a `kind` attribute with the class of the error, one of "timeout",
+# "connection", "rate_limit", "auth", "server", "http", "decode", "other" or "validation".
+#
+# HyperfuelError                 base class of all errors raised by the client
+# ├── HttpError                  non success status code, `status_code` holds the http status
+# │   ├── AuthError              the server rejected the bearer token (401 or 403)
+# │   ├── RateLimitError         the server rejected the request, too many requests (429)
+# │   └── ServerError            the server failed to handle the request (5xx)
+# ├── TimeoutError               the request to the server timed out, also a builtin TimeoutError
+# ├── DecodeError                the response of the server couldn't be decoded
+# └── QueryValidationError       the query is invalid, `field` holds the path of the offending
+#                                value (e.g. "receipts[2].asset_id[0]") if it is known
+from .hyperfuel import (
+    HyperfuelError,
+    HttpError,
+    AuthError,
+    RateLimitError,
+    ServerError,
+    TimeoutError,
+    DecodeError,
+    QueryValidationError,
+)
+
+__all__ = [
+    "HyperfuelError",
+    "HttpError",
+    "AuthError",
+    "RateLimitError",
+    "ServerError",
+    "TimeoutError",
+    "DecodeError",
+    "QueryValidationError",
+]
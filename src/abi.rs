@@ -0,0 +1,1588 @@
+//! Decoding of Sway log payloads and contract calls using the JSON ABI of the contract.
+//!
+//! Both the legacy ABI format (numeric `typeId`s) and the `specVersion` 1 format
+//! (`concreteTypes`/`metadataTypes`) are supported. Log payloads are decoded using the encoding
+//! declared by the ABI, v0 or v1.
+//!
+//! Calls can only be resolved for ABIs using encoding v0, where param1 of the CALL receipt is the
+//! function selector. With encoding v1 it is a pointer to the function name in the memory of the
+//! VM instead, which isn't part of the receipt. Calls to contracts with a v1 ABI are left
+//! undecoded and a warning is logged once when the ABI is registered.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use hyperfuel_format::{Data, Hash, Hex};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict, PyList, PyLong, PyTuple},
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::response::LogContext;
+use crate::types::{Binary, DecodedCall, Receipt, ReceiptType};
+
+/// ABIs of the contracts whose logs are decoded, keyed by contract id.
+#[derive(Debug, Default)]
+pub struct AbiRegistry {
+    contracts: HashMap<String, ContractAbi>,
+    native_types: bool,
+}
+
+impl AbiRegistry {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut contracts = HashMap::new();
+
+        for (contract_id, json) in config.abis.iter().flatten() {
+            let id = Hash::decode_hex(contract_id)
+                .with_context(|| format!("parse contract id {}", contract_id))?;
+            let abi = ContractAbi::parse(json)
+                .with_context(|| format!("parse abi of contract {}", contract_id))?;
+            if abi.encoding == Encoding::V1 {
+                log::warn!(
+                    "calls to contract {} won't be decoded, its abi uses encoding v1 where the \
+                     CALL receipt doesn't hold the function selector",
+                    contract_id
+                );
+            }
+            contracts.insert(id.encode_hex(), abi);
+        }
+
+        Ok(Self {
+            contracts,
+            native_types: config.native_types.unwrap_or_default(),
+        })
+    }
+
+    /// Sets the `decoded` field of the LogData receipts emitted by a contract that has an ABI.
+    ///
+    /// Logs that fail to decode are left as they are and a warning is logged.
+    pub fn decode_logs(&self, logs: &mut [LogContext]) {
+        if self.contracts.is_empty() {
+            return;
+        }
+
+        Python::with_gil(|py| self.decode_logs_with_gil(py, logs))
+    }
+
+    fn decode_logs_with_gil(&self, py: Python, logs: &mut [LogContext]) {
+        for log in logs.iter_mut() {
+            match self.decode_log(log) {
+                Ok(Some(token)) => match token.to_object(py, self.native_types) {
+                    Ok(obj) => log.decoded = Some(obj),
+                    Err(e) => log::warn!("failed to convert decoded log to python: {}", e),
+                },
+                Ok(None) => (),
+                Err(e) => log::warn!(
+                    "failed to decode log {} of tx {}: {:?}",
+                    log.receipt_index,
+                    log.tx_id,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Sets the `decoded_call` field of the CALL receipts to a contract that has an ABI.
+    ///
+    /// Calls that fail to decode are left as they are and a warning is logged.
+    pub fn decode_calls(&self, receipts: &mut [Receipt]) {
+        if self.contracts.is_empty() {
+            return;
+        }
+
+        Python::with_gil(|py| {
+            for receipt in receipts.iter_mut() {
+                match self.decode_call(py, receipt) {
+                    Ok(call) => receipt.decoded_call = call,
+                    Err(e) => log::warn!(
+                        "failed to decode call {} of tx {}: {:?}",
+                        receipt.receipt_index,
+                        receipt.tx_id,
+                        e
+                    ),
+                }
+            }
+        })
+    }
+
+    fn decode_call(&self, py: Python, receipt: &Receipt) -> Result<Option<DecodedCall>> {
+        if receipt.receipt_type != ReceiptType::Call {
+            return Ok(None);
+        }
+        let abi = match receipt
+            .to
+            .as_ref()
+            .and_then(|to| self.contracts.get(&to.to_hex()))
+        {
+            // v1 abis were already reported when they were registered
+            Some(abi) if abi.encoding == Encoding::V0 => abi,
+            _ => return Ok(None),
+        };
+        let (param1, param2) = match (receipt.param1, receipt.param2) {
+            (Some(param1), Some(param2)) => (param1, param2),
+            _ => return Ok(None),
+        };
+
+        let (function, args) = match abi.decode_call(param1, param2)? {
+            Some(call) => call,
+            None => return Ok(None),
+        };
+        let args = args
+            .map(|args| args.to_object(py, self.native_types))
+            .transpose()?;
+
+        Ok(Some(DecodedCall {
+            function: function.to_owned(),
+            selector: param1,
+            args,
+        }))
+    }
+
+    fn decode_log(&self, log: &LogContext) -> Result<Option<Token>> {
+        let contract_id = match log.root_contract_id.as_ref().or(log.contract_id.as_ref()) {
+            Some(id) => id.to_hex(),
+            None => return Ok(None),
+        };
+        let abi = match self.contracts.get(&contract_id) {
+            Some(abi) => abi,
+            None => return Ok(None),
+        };
+        let (log_id, data) = match (log.rb, &log.data) {
+            (Some(rb), Some(data)) => (rb, data),
+            _ => return Ok(None),
+        };
+
+        let data = match data {
+            Binary::Hex(hex) => Data::decode_hex(hex)
+                .context("decode data hex")?
+                .as_ref()
+                .to_vec(),
+            Binary::Bytes(bytes) => bytes.clone(),
+        };
+        abi.decode_log(log_id, &data).map(Some)
+    }
+}
+
+/// The parts of a contract ABI needed for decoding.
+#[derive(Debug)]
+pub struct ContractAbi {
+    encoding: Encoding,
+    logged_types: HashMap<u64, ParamType>,
+    /// Functions keyed by selector, only filled for encoding v0
+    functions: HashMap<u64, Function>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    V0,
+    V1,
+}
+
+impl Encoding {
+    /// Legacy ABIs without an encoding field predate encoding v1.
+    fn parse(version: Option<&str>) -> Result<Self> {
+        match version {
+            None | Some("0") => Ok(Self::V0),
+            Some("1") => Ok(Self::V1),
+            Some(v) => Err(anyhow!("unsupported abi encoding version {}", v)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Function {
+    name: String,
+    inputs: Vec<(String, ParamType)>,
+}
+
+impl ContractAbi {
+    pub fn parse(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json).context("parse json")?;
+        let (encoding, types) = if value.get("specVersion").is_some() {
+            let abi: SpecAbi = serde_json::from_value(value).context("parse abi")?;
+            let encoding = Encoding::parse(Some(abi.encoding_version.as_deref().unwrap_or("1")))?;
+            (encoding, TypeTable::from_spec(abi)?)
+        } else {
+            let abi: LegacyAbi = serde_json::from_value(value).context("parse abi")?;
+            let encoding = Encoding::parse(abi.encoding.as_deref())?;
+            (encoding, TypeTable::from_legacy(abi)?)
+        };
+
+        let mut logged_types = HashMap::new();
+        for (log_id, app) in types.logged_types.iter() {
+            let param_type = types
+                .resolve(app, &HashMap::new())
+                .with_context(|| format!("resolve type of log {}", log_id))?;
+            logged_types.insert(*log_id, param_type);
+        }
+
+        let mut functions = HashMap::new();
+        if encoding == Encoding::V0 {
+            for (name, inputs) in types.functions.iter() {
+                let inputs = inputs
+                    .iter()
+                    .map(|app| Ok((app.name.clone(), types.resolve(app, &HashMap::new())?)))
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| format!("resolve inputs of function {}", name))?;
+                let function = Function {
+                    name: name.clone(),
+                    inputs,
+                };
+                functions.insert(function.selector(), function);
+            }
+        }
+
+        Ok(Self {
+            encoding,
+            logged_types,
+            functions,
+        })
+    }
+
+    /// Decodes the data of a LogData receipt, `log_id` is the rb register of the receipt.
+    pub fn decode_log(&self, log_id: u64, data: &[u8]) -> Result<Token> {
+        let param_type = self
+            .logged_types
+            .get(&log_id)
+            .ok_or_else(|| anyhow!("log id {} not found in abi", log_id))?;
+
+        let mut decoder = Decoder::new(data, self.encoding);
+        let token = decoder.decode(param_type)?;
+        decoder.finish()?;
+
+        Ok(token)
+    }
+
+    /// Resolves the called function from param1 of a CALL receipt and reads the arguments from
+    /// param2 if possible, returns None if the selector isn't in the ABI.
+    ///
+    /// Only ABIs using encoding v0 are supported, an error is returned for encoding v1.
+    pub fn decode_call(&self, param1: u64, param2: u64) -> Result<Option<(&str, Option<Token>)>> {
+        if self.encoding == Encoding::V1 {
+            return Err(anyhow!(
+                "calls can't be decoded with abis using encoding v1, param1 of the CALL receipt \
+                 is a pointer into vm memory instead of the function selector"
+            ));
+        }
+
+        let function = match self.functions.get(&param1) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+
+        // param2 holds the argument itself if it is a single value that fits into a register,
+        // otherwise it's a pointer into vm memory and the arguments can't be read
+        let args = match function.inputs.as_slice() {
+            [] => Some(Token::Struct(Vec::new())),
+            [(name, param_type)] if param_type.is_copy() => {
+                let word = param2.to_be_bytes();
+                let width = param_type.width_v0()?;
+                let mut decoder = Decoder::new(&word[WORD_SIZE - width..], Encoding::V0);
+                let token = decoder
+                    .decode(param_type)
+                    .with_context(|| format!("decode argument {} of {}", name, function.name))?;
+                Some(Token::Struct(vec![(name.clone(), token)]))
+            }
+            _ => None,
+        };
+
+        Ok(Some((function.name.as_str(), args)))
+    }
+}
+
+impl Function {
+    /// First 4 bytes of the sha256 hash of the signature, e.g. `transfer(u64,s(b256))`, as a word.
+    fn selector(&self) -> u64 {
+        let signature = format!("{}({})", self.name, signature_of(&self.inputs_types()));
+        let hash = Sha256::digest(signature.as_bytes());
+        u64::from(u32::from_be_bytes(hash[..4].try_into().unwrap()))
+    }
+
+    fn inputs_types(&self) -> Vec<ParamType> {
+        self.inputs.iter().map(|(_, t)| t.clone()).collect()
+    }
+}
+
+/// Comma separated signatures of the types, as used for function selectors.
+fn signature_of(types: &[ParamType]) -> String {
+    types
+        .iter()
+        .map(|t| t.signature())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyAbi {
+    types: Vec<LegacyTypeDecl>,
+    #[serde(default)]
+    functions: Vec<LegacyFunction>,
+    #[serde(default)]
+    logged_types: Vec<LegacyLoggedType>,
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyTypeDecl {
+    type_id: usize,
+    #[serde(rename = "type")]
+    type_field: String,
+    components: Option<Vec<LegacyTypeApplication>>,
+    type_parameters: Option<Vec<usize>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyTypeApplication {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    type_id: usize,
+    type_arguments: Option<Vec<LegacyTypeApplication>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyFunction {
+    name: String,
+    inputs: Vec<LegacyTypeApplication>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyLoggedType {
+    log_id: LogId,
+    logged_type: LegacyTypeApplication,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpecAbi {
+    encoding_version: Option<String>,
+    concrete_types: Vec<ConcreteType>,
+    #[serde(default)]
+    metadata_types: Vec<MetadataType>,
+    #[serde(default)]
+    logged_types: Vec<SpecLoggedType>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConcreteType {
+    #[serde(rename = "type")]
+    type_field: String,
+    concrete_type_id: String,
+    metadata_type_id: Option<usize>,
+    type_arguments: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetadataType {
+    #[serde(rename = "type")]
+    type_field: String,
+    metadata_type_id: usize,
+    components: Option<Vec<SpecTypeApplication>>,
+    type_parameters: Option<Vec<usize>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpecTypeApplication {
+    #[serde(default)]
+    name: String,
+    type_id: SpecTypeId,
+    type_arguments: Option<Vec<SpecTypeApplication>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpecTypeId {
+    Metadata(usize),
+    Concrete(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpecLoggedType {
+    log_id: LogId,
+    concrete_type_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LogId {
+    Number(u64),
+    String(String),
+}
+
+impl LogId {
+    fn parse(&self) -> Result<u64> {
+        match self {
+            Self::Number(id) => Ok(*id),
+            Self::String(id) => id.parse().with_context(|| format!("parse log id {}", id)),
+        }
+    }
+}
+
+/// Type declarations of an ABI, both formats are converted into this.
+#[derive(Default)]
+struct TypeTable {
+    types: HashMap<usize, TypeDecl>,
+    logged_types: Vec<(u64, TypeApp)>,
+    functions: Vec<(String, Vec<TypeApp>)>,
+}
+
+struct TypeDecl {
+    type_field: String,
+    components: Vec<TypeApp>,
+    type_parameters: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct TypeApp {
+    name: String,
+    type_id: usize,
+    type_arguments: Vec<TypeApp>,
+}
+
+impl From<LegacyTypeApplication> for TypeApp {
+    fn from(app: LegacyTypeApplication) -> Self {
+        Self {
+            name: app.name,
+            type_id: app.type_id,
+            type_arguments: app
+                .type_arguments
+                .unwrap_or_default()
+                .into_iter()
+                .map(Self::from)
+                .collect(),
+        }
+    }
+}
+
+impl TypeTable {
+    fn from_legacy(abi: LegacyAbi) -> Result<Self> {
+        let types = abi
+            .types
+            .into_iter()
+            .map(|decl| {
+                let components = decl
+                    .components
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(TypeApp::from)
+                    .collect();
+                (
+                    decl.type_id,
+                    TypeDecl {
+                        type_field: decl.type_field,
+                        components,
+                        type_parameters: decl.type_parameters.unwrap_or_default(),
+                    },
+                )
+            })
+            .collect();
+
+        let logged_types = abi
+            .logged_types
+            .into_iter()
+            .map(|t| Ok((t.log_id.parse()?, t.logged_type.into())))
+            .collect::<Result<_>>()?;
+
+        let functions = abi
+            .functions
+            .into_iter()
+            .map(|f| (f.name, f.inputs.into_iter().map(TypeApp::from).collect()))
+            .collect();
+
+        Ok(Self {
+            types,
+            logged_types,
+            functions,
+        })
+    }
+
+    fn from_spec(abi: SpecAbi) -> Result<Self> {
+        let mut table = Self::default();
+
+        let concrete_types: HashMap<&str, &ConcreteType> = abi
+            .concrete_types
+            .iter()
+            .map(|t| (t.concrete_type_id.as_str(), t))
+            .collect();
+
+        // concrete types without a metadata type (e.g. u64) get an id after the metadata types
+        let mut next_id = abi
+            .metadata_types
+            .iter()
+            .map(|t| t.metadata_type_id + 1)
+            .max()
+            .unwrap_or(0);
+        let mut concrete_ids = HashMap::new();
+        for t in abi.concrete_types.iter() {
+            if t.metadata_type_id.is_none() {
+                concrete_ids.insert(t.concrete_type_id.as_str(), next_id);
+                table.types.insert(
+                    next_id,
+                    TypeDecl {
+                        type_field: t.type_field.clone(),
+                        components: Vec::new(),
+                        type_parameters: Vec::new(),
+                    },
+                );
+                next_id += 1;
+            }
+        }
+
+        let concrete_app =
+            |id: &str| -> Result<TypeApp> { resolve_concrete(id, &concrete_types, &concrete_ids) };
+
+        for t in abi.metadata_types.iter() {
+            let components = t
+                .components
+                .iter()
+                .flatten()
+                .map(|c| spec_app(c, &concrete_app))
+                .collect::<Result<_>>()
+                .with_context(|| format!("resolve components of {}", t.type_field))?;
+            table.types.insert(
+                t.metadata_type_id,
+                TypeDecl {
+                    type_field: t.type_field.clone(),
+                    components,
+                    type_parameters: t.type_parameters.clone().unwrap_or_default(),
+                },
+            );
+        }
+
+        for t in abi.logged_types.iter() {
+            let app = concrete_app(&t.concrete_type_id)?;
+            table.logged_types.push((t.log_id.parse()?, app));
+        }
+
+        Ok(table)
+    }
+
+    /// Resolves a type application into a concrete type, `generics` maps the ids of the
+    /// generic type parameters in scope to their concrete types.
+    fn resolve(&self, app: &TypeApp, generics: &HashMap<usize, ParamType>) -> Result<ParamType> {
+        let decl = self
+            .types
+            .get(&app.type_id)
+            .ok_or_else(|| anyhow!("type id {} not found", app.type_id))?;
+        let type_field = decl.type_field.as_str();
+
+        if type_field.starts_with("generic ") {
+            return generics
+                .get(&app.type_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("unresolved {}", type_field));
+        }
+
+        let args = app
+            .type_arguments
+            .iter()
+            .map(|a| self.resolve(a, generics))
+            .collect::<Result<Vec<_>>>()?;
+        let inner_generics: HashMap<usize, ParamType> = decl
+            .type_parameters
+            .iter()
+            .copied()
+            .zip(args.iter().cloned())
+            .collect();
+
+        let resolve_components = || -> Result<Vec<(String, ParamType)>> {
+            decl.components
+                .iter()
+                .map(|c| Ok((c.name.clone(), self.resolve(c, &inner_generics)?)))
+                .collect()
+        };
+
+        let param_type = match type_field {
+            "()" => ParamType::Unit,
+            "bool" => ParamType::Bool,
+            "u8" => ParamType::U8,
+            "u16" => ParamType::U16,
+            "u32" => ParamType::U32,
+            "u64" => ParamType::U64,
+            "u256" => ParamType::U256,
+            "b256" => ParamType::B256,
+            "str" => ParamType::StringSlice,
+            "raw untyped slice" => ParamType::RawSlice,
+            t if t.starts_with("str[") => ParamType::StringArray(parse_len(t)?),
+            t if t.starts_with('[') => {
+                let elem = decl
+                    .components
+                    .first()
+                    .ok_or_else(|| anyhow!("array {} has no element type", t))?;
+                ParamType::Array(
+                    Box::new(self.resolve(elem, &inner_generics)?),
+                    parse_len(t)?,
+                )
+            }
+            t if t.starts_with('(') => {
+                ParamType::Tuple(resolve_components()?.into_iter().map(|(_, t)| t).collect())
+            }
+            t if t.starts_with("struct ") => {
+                let name = type_name(t, "struct ");
+                match name.rsplit("::").next().unwrap_or(name) {
+                    "Vec" => {
+                        let elem = args
+                            .into_iter()
+                            .next()
+                            .ok_or_else(|| anyhow!("{} has no type argument", t))?;
+                        ParamType::Vector(Box::new(elem))
+                    }
+                    "String" => ParamType::String,
+                    "Bytes" => ParamType::Bytes,
+                    _ => ParamType::Struct {
+                        fields: resolve_components()?,
+                        generics: args,
+                    },
+                }
+            }
+            t if t.starts_with("enum ") => ParamType::Enum {
+                name: type_name(t, "enum ").to_owned(),
+                variants: resolve_components()?,
+                generics: args,
+            },
+            t => return Err(anyhow!("unsupported type {}", t)),
+        };
+
+        Ok(param_type)
+    }
+}
+
+fn resolve_concrete(
+    id: &str,
+    concrete_types: &HashMap<&str, &ConcreteType>,
+    concrete_ids: &HashMap<&str, usize>,
+) -> Result<TypeApp> {
+    let t = concrete_types
+        .get(id)
+        .ok_or_else(|| anyhow!("concrete type {} not found", id))?;
+
+    let type_id = match t.metadata_type_id {
+        Some(id) => id,
+        None => concrete_ids[id],
+    };
+    let type_arguments = t
+        .type_arguments
+        .iter()
+        .flatten()
+        .map(|a| resolve_concrete(a, concrete_types, concrete_ids))
+        .collect::<Result<_>>()?;
+
+    Ok(TypeApp {
+        name: String::new(),
+        type_id,
+        type_arguments,
+    })
+}
+
+fn spec_app(
+    app: &SpecTypeApplication,
+    concrete_app: &impl Fn(&str) -> Result<TypeApp>,
+) -> Result<TypeApp> {
+    match &app.type_id {
+        SpecTypeId::Concrete(id) => Ok(TypeApp {
+            name: app.name.clone(),
+            ..concrete_app(id)?
+        }),
+        SpecTypeId::Metadata(id) => Ok(TypeApp {
+            name: app.name.clone(),
+            type_id: *id,
+            type_arguments: app
+                .type_arguments
+                .iter()
+                .flatten()
+                .map(|a| spec_app(a, concrete_app))
+                .collect::<Result<_>>()?,
+        }),
+    }
+}
+
+/// Strips the prefix and any generic arguments, e.g. `struct std::vec::Vec<u64>` => `std::vec::Vec`
+fn type_name<'a>(type_field: &'a str, prefix: &str) -> &'a str {
+    let name = &type_field[prefix.len()..];
+    name.split('<').next().unwrap_or(name).trim()
+}
+
+/// Parses the length of `str[N]` and `[_; N]` types.
+fn parse_len(type_field: &str) -> Result<usize> {
+    let digits: String = type_field
+        .trim_end_matches(']')
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    digits
+        .parse()
+        .with_context(|| format!("parse length of {}", type_field))
+}
+
+/// A concrete Sway type.
+#[derive(Debug, Clone)]
+pub enum ParamType {
+    Unit,
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U256,
+    B256,
+    StringArray(usize),
+    StringSlice,
+    String,
+    Bytes,
+    RawSlice,
+    Vector(Box<ParamType>),
+    Array(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+    Struct {
+        fields: Vec<(String, ParamType)>,
+        generics: Vec<ParamType>,
+    },
+    Enum {
+        name: String,
+        variants: Vec<(String, ParamType)>,
+        generics: Vec<ParamType>,
+    },
+}
+
+const WORD_SIZE: usize = 8;
+
+/// Rounds the length up to whole words.
+fn word_align(len: usize) -> usize {
+    len.div_ceil(WORD_SIZE) * WORD_SIZE
+}
+
+/// Sums the word aligned sizes of the fields of a struct or tuple.
+fn fields_width_v0<'a>(mut fields: impl Iterator<Item = &'a ParamType>) -> Result<usize> {
+    fields.try_fold(0usize, |acc, t| {
+        acc.checked_add(word_align(t.width_v0()?))
+            .ok_or_else(|| anyhow!("type is too large"))
+    })
+}
+
+impl ParamType {
+    /// Whether the value is passed in a register instead of by reference.
+    fn is_copy(&self) -> bool {
+        matches!(
+            self,
+            Self::Unit | Self::Bool | Self::U8 | Self::U16 | Self::U32 | Self::U64
+        )
+    }
+
+    /// Minimum size of an encoding v1 value, used to bound lengths read from the data.
+    fn min_size(&self) -> usize {
+        match self {
+            Self::Unit => 0,
+            Self::Bool | Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 => 4,
+            Self::U64 => 8,
+            Self::U256 | Self::B256 => 32,
+            Self::StringArray(len) => *len,
+            // the length prefix
+            Self::StringSlice | Self::String | Self::Bytes | Self::RawSlice | Self::Vector(_) => 8,
+            Self::Array(elem, len) => elem.min_size().saturating_mul(*len),
+            Self::Tuple(elems) => elems
+                .iter()
+                .fold(0, |acc, t| acc.saturating_add(t.min_size())),
+            Self::Struct { fields, .. } => fields
+                .iter()
+                .fold(0, |acc, (_, t)| acc.saturating_add(t.min_size())),
+            // the discriminant
+            Self::Enum { .. } => 8,
+        }
+    }
+
+    /// Size of the type in encoding v0. Everything but u8, bool and unit takes whole words,
+    /// struct and tuple fields are word aligned and enums are the discriminant followed by
+    /// the value, right aligned to the size of the largest variant.
+    fn width_v0(&self) -> Result<usize> {
+        let width = match self {
+            Self::Unit | Self::Bool | Self::U8 => 1,
+            Self::U16 | Self::U32 | Self::U64 => WORD_SIZE,
+            Self::U256 | Self::B256 => 32,
+            Self::StringArray(len) => word_align(*len),
+            Self::StringSlice | Self::RawSlice => 2 * WORD_SIZE,
+            Self::String | Self::Bytes | Self::Vector(_) => 3 * WORD_SIZE,
+            Self::Array(elem, len) => elem
+                .width_v0()?
+                .checked_mul(*len)
+                .ok_or_else(|| anyhow!("type is too large"))?,
+            Self::Tuple(elems) => fields_width_v0(elems.iter())?,
+            Self::Struct { fields, .. } => fields_width_v0(fields.iter().map(|(_, t)| t))?,
+            Self::Enum { variants, .. } => {
+                if is_unit_enum(variants) {
+                    WORD_SIZE
+                } else {
+                    let max = variants
+                        .iter()
+                        .map(|(_, t)| t.width_v0())
+                        .try_fold(0, |acc, w| w.map(|w| acc.max(w)))?;
+                    WORD_SIZE + word_align(max)
+                }
+            }
+        };
+
+        Ok(width)
+    }
+
+    /// Signature of the type as used by encoding v0 function selectors.
+    fn signature(&self) -> String {
+        let with_generics = |prefix: &str, generics: &[ParamType], inner: &[ParamType]| {
+            if generics.is_empty() {
+                format!("{}({})", prefix, signature_of(inner))
+            } else {
+                format!(
+                    "{}<{}>({})",
+                    prefix,
+                    signature_of(generics),
+                    signature_of(inner)
+                )
+            }
+        };
+
+        match self {
+            Self::Unit => "()".to_owned(),
+            Self::Bool => "bool".to_owned(),
+            Self::U8 => "u8".to_owned(),
+            Self::U16 => "u16".to_owned(),
+            Self::U32 => "u32".to_owned(),
+            Self::U64 => "u64".to_owned(),
+            Self::U256 => "u256".to_owned(),
+            Self::B256 => "b256".to_owned(),
+            Self::StringArray(len) => format!("str[{}]", len),
+            Self::StringSlice => "str".to_owned(),
+            Self::String => "s(s(s(rawptr,u64),u64))".to_owned(),
+            Self::Bytes => "s(s(rawptr,u64),u64)".to_owned(),
+            Self::RawSlice => "rawslice".to_owned(),
+            Self::Vector(elem) => {
+                let elem = elem.signature();
+                format!("s<{0}>(s<{0}>(rawptr,u64),u64)", elem)
+            }
+            Self::Array(elem, len) => format!("a[{};{}]", elem.signature(), len),
+            Self::Tuple(elems) => format!("({})", signature_of(elems)),
+            Self::Struct { fields, generics } => {
+                let fields: Vec<_> = fields.iter().map(|(_, t)| t.clone()).collect();
+                with_generics("s", generics, &fields)
+            }
+            Self::Enum {
+                variants, generics, ..
+            } => {
+                let variants: Vec<_> = variants.iter().map(|(_, t)| t.clone()).collect();
+                with_generics("e", generics, &variants)
+            }
+        }
+    }
+}
+
+/// A decoded value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Unit,
+    Bool(bool),
+    U64(u64),
+    U256([u8; 32]),
+    B256([u8; 32]),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Token>),
+    Tuple(Vec<Token>),
+    Struct(Vec<(String, Token)>),
+    Enum {
+        name: String,
+        variant: String,
+        value: Box<Token>,
+    },
+}
+
+impl Token {
+    /// Converts the value into python.
+    ///
+    /// Structs become dicts, `Option` becomes the value or None and other enums become a dict with
+    /// the variant name as the only key. b256 and bytes are hex strings like the rest of the response,
+    /// or bytes with `native_types`.
+    pub fn to_object(&self, py: Python, native_types: bool) -> PyResult<PyObject> {
+        let obj = match self {
+            Self::Unit => py.None(),
+            Self::Bool(v) => v.into_py(py),
+            Self::U64(v) => v.into_py(py),
+            Self::U256(v) => py
+                .get_type::<PyLong>()
+                .call_method1("from_bytes", (PyBytes::new(py, v), "big"))?
+                .into_py(py),
+            Self::B256(v) if native_types => PyBytes::new(py, v).into_py(py),
+            Self::B256(v) => Hash::from(*v).encode_hex().into_py(py),
+            Self::String(v) => v.into_py(py),
+            Self::Bytes(v) if native_types => PyBytes::new(py, v).into_py(py),
+            Self::Bytes(v) => Data::from(v.clone()).encode_hex().into_py(py),
+            Self::List(tokens) => {
+                let items = tokens
+                    .iter()
+                    .map(|t| t.to_object(py, native_types))
+                    .collect::<PyResult<Vec<_>>>()?;
+                PyList::new(py, items).into_py(py)
+            }
+            Self::Tuple(tokens) => {
+                let items = tokens
+                    .iter()
+                    .map(|t| t.to_object(py, native_types))
+                    .collect::<PyResult<Vec<_>>>()?;
+                PyTuple::new(py, items).into_py(py)
+            }
+            Self::Struct(fields) => {
+                let dict = PyDict::new(py);
+                for (name, token) in fields.iter() {
+                    dict.set_item(name, token.to_object(py, native_types)?)?;
+                }
+                dict.into_py(py)
+            }
+            Self::Enum {
+                name,
+                variant,
+                value,
+            } => {
+                let is_option = name.rsplit("::").next() == Some("Option");
+                if is_option && variant == "None" {
+                    py.None()
+                } else if is_option {
+                    value.to_object(py, native_types)?
+                } else {
+                    let dict = PyDict::new(py);
+                    dict.set_item(variant, value.to_object(py, native_types)?)?;
+                    dict.into_py(py)
+                }
+            }
+        };
+
+        Ok(obj)
+    }
+}
+
+/// Enums with only unit variants are encoded as just the discriminant in encoding v0.
+fn is_unit_enum(variants: &[(String, ParamType)]) -> bool {
+    variants.iter().all(|(_, t)| matches!(t, ParamType::Unit))
+}
+
+/// Decoder of log payloads.
+///
+/// Encoding v1 is big endian with dynamically sized values prefixed by their length as a u64.
+/// Encoding v0 is big endian with values padded to words (see [ParamType::width_v0]),
+/// dynamically sized values aren't length prefixed and take the rest of the data so they can
+/// only be decoded as the last value.
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    encoding: Encoding,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8], encoding: Encoding) -> Self {
+        Self {
+            data,
+            pos: 0,
+            encoding,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Checks that all of the data was decoded, encoding v0 allows zero padding to a whole word.
+    fn finish(&self) -> Result<()> {
+        let trailing = &self.data[self.pos..];
+        let is_padding = self.encoding == Encoding::V0
+            && trailing.len() < WORD_SIZE
+            && trailing.iter().all(|b| *b == 0);
+
+        if trailing.is_empty() || is_padding {
+            Ok(())
+        } else {
+            Err(anyhow!("{} trailing bytes after decoding", trailing.len()))
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("unexpected end of data at byte {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, size: usize) -> Result<u64> {
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = self.uint(8)?;
+        usize::try_from(len).context("length doesn't fit into usize")
+    }
+
+    fn bool(&mut self) -> Result<Token> {
+        match self.uint(1)? {
+            0 => Ok(Token::Bool(false)),
+            1 => Ok(Token::Bool(true)),
+            v => Err(anyhow!("invalid bool value {}", v)),
+        }
+    }
+
+    fn string(&mut self, len: usize) -> Result<Token> {
+        let bytes = self.take(len)?;
+        let s = std::str::from_utf8(bytes).context("decode utf8 string")?;
+        Ok(Token::String(s.to_owned()))
+    }
+
+    /// Decodes a length prefixed list, the length is checked against the remaining data
+    /// before anything is allocated.
+    fn vector(&mut self, elem: &ParamType) -> Result<Vec<Token>> {
+        let len = self.len()?;
+        let max_len = match elem.min_size() {
+            // zero sized values take no data, so only the size of the payload bounds them
+            0 => self.data.len(),
+            size => self.remaining() / size,
+        };
+        if len > max_len {
+            return Err(anyhow!(
+                "length {} at byte {} doesn't fit into the remaining {} bytes",
+                len,
+                self.pos - 8,
+                self.remaining()
+            ));
+        }
+
+        self.list(elem, len)
+    }
+
+    fn list(&mut self, elem: &ParamType, len: usize) -> Result<Vec<Token>> {
+        let mut tokens = Vec::with_capacity(std::cmp::min(len, self.data.len()));
+        for _ in 0..len {
+            tokens.push(self.decode(elem)?);
+        }
+        Ok(tokens)
+    }
+
+    fn variant<'t>(
+        name: &str,
+        variants: &'t [(String, ParamType)],
+        discriminant: u64,
+    ) -> Result<&'t (String, ParamType)> {
+        usize::try_from(discriminant)
+            .ok()
+            .and_then(|i| variants.get(i))
+            .ok_or_else(|| anyhow!("invalid discriminant {} of {}", discriminant, name))
+    }
+
+    fn decode(&mut self, param_type: &ParamType) -> Result<Token> {
+        match self.encoding {
+            Encoding::V0 => self.decode_v0(param_type),
+            Encoding::V1 => self.decode_v1(param_type),
+        }
+    }
+
+    fn decode_v1(&mut self, param_type: &ParamType) -> Result<Token> {
+        let token = match param_type {
+            ParamType::Unit => Token::Unit,
+            ParamType::Bool => self.bool()?,
+            ParamType::U8 => Token::U64(self.uint(1)?),
+            ParamType::U16 => Token::U64(self.uint(2)?),
+            ParamType::U32 => Token::U64(self.uint(4)?),
+            ParamType::U64 => Token::U64(self.uint(8)?),
+            ParamType::U256 => Token::U256(self.take(32)?.try_into().unwrap()),
+            ParamType::B256 => Token::B256(self.take(32)?.try_into().unwrap()),
+            ParamType::StringArray(len) => self.string(*len)?,
+            ParamType::StringSlice | ParamType::String => {
+                let len = self.len()?;
+                self.string(len)?
+            }
+            ParamType::Bytes | ParamType::RawSlice => {
+                let len = self.len()?;
+                Token::Bytes(self.take(len)?.to_vec())
+            }
+            ParamType::Vector(elem) => Token::List(self.vector(elem)?),
+            ParamType::Array(elem, len) => Token::List(self.list(elem, *len)?),
+            ParamType::Tuple(elems) => Token::Tuple(
+                elems
+                    .iter()
+                    .map(|t| self.decode(t))
+                    .collect::<Result<_>>()?,
+            ),
+            ParamType::Struct { fields, .. } => Token::Struct(
+                fields
+                    .iter()
+                    .map(|(name, t)| Ok((name.clone(), self.decode(t)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            ParamType::Enum { name, variants, .. } => {
+                let discriminant = self.uint(8)?;
+                let (variant, t) = Self::variant(name, variants, discriminant)?;
+                Token::Enum {
+                    name: name.clone(),
+                    variant: variant.clone(),
+                    value: Box::new(self.decode(t)?),
+                }
+            }
+        };
+
+        Ok(token)
+    }
+
+    /// Decodes a struct or tuple field and skips the padding up to the next word.
+    fn field_v0(&mut self, param_type: &ParamType) -> Result<Token> {
+        let start = self.pos;
+        let token = self.decode(param_type)?;
+        let len = self.pos - start;
+        self.take(word_align(len) - len)?;
+        Ok(token)
+    }
+
+    fn decode_v0(&mut self, param_type: &ParamType) -> Result<Token> {
+        let token = match param_type {
+            ParamType::Unit => {
+                self.take(1)?;
+                Token::Unit
+            }
+            ParamType::Bool => self.bool()?,
+            ParamType::U8 => Token::U64(self.uint(1)?),
+            ParamType::U16 | ParamType::U32 | ParamType::U64 => Token::U64(self.uint(WORD_SIZE)?),
+            ParamType::U256 => Token::U256(self.take(32)?.try_into().unwrap()),
+            ParamType::B256 => Token::B256(self.take(32)?.try_into().unwrap()),
+            ParamType::StringArray(len) => {
+                let token = self.string(*len)?;
+                self.take(word_align(*len) - len)?;
+                token
+            }
+            ParamType::StringSlice | ParamType::String => self.string(self.remaining())?,
+            ParamType::Bytes | ParamType::RawSlice => {
+                Token::Bytes(self.take(self.remaining())?.to_vec())
+            }
+            ParamType::Vector(elem) => {
+                let width = elem.width_v0()?;
+                if width == 0 || !self.remaining().is_multiple_of(width) {
+                    return Err(anyhow!(
+                        "{} bytes at byte {} aren't a list of {} byte values",
+                        self.remaining(),
+                        self.pos,
+                        width
+                    ));
+                }
+                Token::List(self.list(elem, self.remaining() / width)?)
+            }
+            ParamType::Array(elem, len) => Token::List(self.list(elem, *len)?),
+            ParamType::Tuple(elems) => Token::Tuple(
+                elems
+                    .iter()
+                    .map(|t| self.field_v0(t))
+                    .collect::<Result<_>>()?,
+            ),
+            ParamType::Struct { fields, .. } => Token::Struct(
+                fields
+                    .iter()
+                    .map(|(name, t)| Ok((name.clone(), self.field_v0(t)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            ParamType::Enum { name, variants, .. } => {
+                let width = param_type.width_v0()?;
+                let discriminant = self.uint(WORD_SIZE)?;
+                let (variant, t) = Self::variant(name, variants, discriminant)?;
+                let value = if is_unit_enum(variants) {
+                    Token::Unit
+                } else {
+                    self.take(width - WORD_SIZE - t.width_v0()?)?;
+                    self.decode(t)?
+                };
+                Token::Enum {
+                    name: name.clone(),
+                    variant: variant.clone(),
+                    value: Box::new(value),
+                }
+            }
+        };
+
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(v: u64) -> Vec<u8> {
+        v.to_be_bytes().to_vec()
+    }
+
+    fn decode(encoding: Encoding, param_type: &ParamType, data: &[u8]) -> Result<Token> {
+        let mut decoder = Decoder::new(data, encoding);
+        let token = decoder.decode(param_type)?;
+        decoder.finish()?;
+        Ok(token)
+    }
+
+    fn option(t: ParamType) -> ParamType {
+        ParamType::Enum {
+            name: "std::option::Option".to_owned(),
+            variants: vec![("None".to_owned(), ParamType::Unit), ("Some".to_owned(), t)],
+            generics: Vec::new(),
+        }
+    }
+
+    fn some(name: &str, variant: &str, value: Token) -> Token {
+        Token::Enum {
+            name: name.to_owned(),
+            variant: variant.to_owned(),
+            value: Box::new(value),
+        }
+    }
+
+    fn transfer() -> ParamType {
+        ParamType::Struct {
+            fields: vec![
+                ("flag".to_owned(), ParamType::U8),
+                ("amount".to_owned(), ParamType::U64),
+                ("memo".to_owned(), option(ParamType::U64)),
+            ],
+            generics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn v1_struct() {
+        let data = [vec![1], word(100), word(1), word(7)].concat();
+
+        let token = decode(Encoding::V1, &transfer(), &data).unwrap();
+        assert_eq!(
+            token,
+            Token::Struct(vec![
+                ("flag".to_owned(), Token::U64(1)),
+                ("amount".to_owned(), Token::U64(100)),
+                (
+                    "memo".to_owned(),
+                    some("std::option::Option", "Some", Token::U64(7))
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn v1_option_and_result() {
+        let option = option(ParamType::U64);
+        assert_eq!(
+            decode(Encoding::V1, &option, &word(0)).unwrap(),
+            some("std::option::Option", "None", Token::Unit)
+        );
+
+        let result = ParamType::Enum {
+            name: "std::result::Result".to_owned(),
+            variants: vec![
+                ("Ok".to_owned(), ParamType::U32),
+                ("Err".to_owned(), ParamType::String),
+            ],
+            generics: Vec::new(),
+        };
+        let data = [word(1), word(3), b"bad".to_vec()].concat();
+        assert_eq!(
+            decode(Encoding::V1, &result, &data).unwrap(),
+            some(
+                "std::result::Result",
+                "Err",
+                Token::String("bad".to_owned())
+            )
+        );
+
+        let err = decode(Encoding::V1, &result, &word(2)).unwrap_err();
+        assert!(err.to_string().contains("invalid discriminant 2"));
+    }
+
+    #[test]
+    fn v1_vec_and_u256() {
+        let vec = ParamType::Vector(Box::new(ParamType::U16));
+        let data = [word(2), vec![0, 1, 0, 2]].concat();
+        assert_eq!(
+            decode(Encoding::V1, &vec, &data).unwrap(),
+            Token::List(vec![Token::U64(1), Token::U64(2)])
+        );
+
+        let mut value = [0u8; 32];
+        value[31] = 5;
+        assert_eq!(
+            decode(Encoding::V1, &ParamType::U256, &value).unwrap(),
+            Token::U256(value)
+        );
+    }
+
+    #[test]
+    fn v1_list_length_is_bounded() {
+        let vec = ParamType::Vector(Box::new(ParamType::U64));
+        let data = [word(3), word(1), word(2)].concat();
+        assert!(decode(Encoding::V1, &vec, &data).is_err());
+
+        let data = [word(u64::MAX), word(1)].concat();
+        assert!(decode(Encoding::V1, &vec, &data).is_err());
+
+        let units = ParamType::Vector(Box::new(ParamType::Unit));
+        assert_eq!(
+            decode(Encoding::V1, &units, &word(2)).unwrap(),
+            Token::List(vec![Token::Unit, Token::Unit])
+        );
+        assert!(decode(Encoding::V1, &units, &word(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let data = [word(1), vec![0]].concat();
+        assert!(decode(Encoding::V1, &ParamType::U64, &data).is_err());
+    }
+
+    #[test]
+    fn v0_struct() {
+        // the u8 is padded to a word, the value of the option is right aligned
+        let data = [vec![1, 0, 0, 0, 0, 0, 0, 0], word(100), word(1), word(7)].concat();
+        assert_eq!(transfer().width_v0().unwrap(), data.len());
+
+        let token = decode(Encoding::V0, &transfer(), &data).unwrap();
+        assert_eq!(
+            token,
+            Token::Struct(vec![
+                ("flag".to_owned(), Token::U64(1)),
+                ("amount".to_owned(), Token::U64(100)),
+                (
+                    "memo".to_owned(),
+                    some("std::option::Option", "Some", Token::U64(7))
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn v0_enum() {
+        let option = option(ParamType::U8);
+        let data = [word(1), word(9)].concat();
+        assert_eq!(
+            decode(Encoding::V0, &option, &data).unwrap(),
+            some("std::option::Option", "Some", Token::U64(9))
+        );
+        let data = [word(0), word(0)].concat();
+        assert_eq!(
+            decode(Encoding::V0, &option, &data).unwrap(),
+            some("std::option::Option", "None", Token::Unit)
+        );
+
+        // only the discriminant is encoded if all variants are unit
+        let color = ParamType::Enum {
+            name: "Color".to_owned(),
+            variants: vec![
+                ("Red".to_owned(), ParamType::Unit),
+                ("Blue".to_owned(), ParamType::Unit),
+            ],
+            generics: Vec::new(),
+        };
+        assert_eq!(
+            decode(Encoding::V0, &color, &word(1)).unwrap(),
+            some("Color", "Blue", Token::Unit)
+        );
+    }
+
+    #[test]
+    fn v0_string_array_and_vec() {
+        let data = [b"abc".to_vec(), vec![0; 5]].concat();
+        assert_eq!(
+            decode(Encoding::V0, &ParamType::StringArray(3), &data).unwrap(),
+            Token::String("abc".to_owned())
+        );
+
+        let vec = ParamType::Vector(Box::new(ParamType::U64));
+        let data = [word(1), word(2)].concat();
+        assert_eq!(
+            decode(Encoding::V0, &vec, &data).unwrap(),
+            Token::List(vec![Token::U64(1), Token::U64(2)])
+        );
+        assert!(decode(Encoding::V0, &vec, &data[..12]).is_err());
+    }
+
+    #[test]
+    fn spec_abi_log() {
+        let json = r#"{
+            "programType": "contract",
+            "specVersion": "1",
+            "encodingVersion": "1",
+            "concreteTypes": [
+                {"type": "()", "concreteTypeId": "unit"},
+                {"type": "u64", "concreteTypeId": "u64"},
+                {"type": "struct Transfer", "concreteTypeId": "transfer", "metadataTypeId": 0},
+                {
+                    "type": "enum std::option::Option<u64>",
+                    "concreteTypeId": "option_u64",
+                    "metadataTypeId": 1,
+                    "typeArguments": ["u64"]
+                }
+            ],
+            "metadataTypes": [
+                {
+                    "type": "struct Transfer",
+                    "metadataTypeId": 0,
+                    "components": [
+                        {"name": "amount", "typeId": "u64"},
+                        {"name": "memo", "typeId": "option_u64"}
+                    ]
+                },
+                {
+                    "type": "enum std::option::Option",
+                    "metadataTypeId": 1,
+                    "components": [
+                        {"name": "None", "typeId": "unit"},
+                        {"name": "Some", "typeId": 2}
+                    ],
+                    "typeParameters": [2]
+                },
+                {"type": "generic T", "metadataTypeId": 2}
+            ],
+            "loggedTypes": [{"logId": "42", "concreteTypeId": "transfer"}]
+        }"#;
+        let abi = ContractAbi::parse(json).unwrap();
+
+        let data = [word(100), word(0)].concat();
+        assert_eq!(
+            abi.decode_log(42, &data).unwrap(),
+            Token::Struct(vec![
+                ("amount".to_owned(), Token::U64(100)),
+                (
+                    "memo".to_owned(),
+                    some("std::option::Option", "None", Token::Unit)
+                ),
+            ])
+        );
+        assert!(abi.decode_log(1, &data).is_err());
+    }
+
+    #[test]
+    fn legacy_abi_log() {
+        let json = r#"{
+            "types": [
+                {"typeId": 0, "type": "u64"},
+                {"typeId": 1, "type": "u8"},
+                {
+                    "typeId": 2,
+                    "type": "struct Event",
+                    "components": [
+                        {"name": "flag", "type": 1},
+                        {"name": "value", "type": 0}
+                    ]
+                }
+            ],
+            "functions": [],
+            "loggedTypes": [{"logId": 0, "loggedType": {"name": "", "type": 2}}]
+        }"#;
+        let abi = ContractAbi::parse(json).unwrap();
+        assert_eq!(abi.encoding, Encoding::V0);
+
+        let data = [vec![1, 0, 0, 0, 0, 0, 0, 0], word(9)].concat();
+        assert_eq!(
+            abi.decode_log(0, &data).unwrap(),
+            Token::Struct(vec![
+                ("flag".to_owned(), Token::U64(1)),
+                ("value".to_owned(), Token::U64(9)),
+            ])
+        );
+    }
+
+    #[test]
+    fn legacy_abi_call() {
+        let json = r#"{
+            "types": [
+                {"typeId": 0, "type": "u64"},
+                {"typeId": 1, "type": "bool"},
+                {
+                    "typeId": 2,
+                    "type": "struct Event",
+                    "components": [{"name": "value", "type": 0}]
+                }
+            ],
+            "functions": [
+                {"name": "set", "inputs": [{"name": "value", "type": 0}]},
+                {"name": "flag", "inputs": [{"name": "on", "type": 1}]},
+                {"name": "emit", "inputs": [{"name": "event", "type": 2}]}
+            ]
+        }"#;
+        let abi = ContractAbi::parse(json).unwrap();
+
+        // first 4 bytes of sha256("set(u64)")
+        let (name, args) = abi.decode_call(2247002193, 5).unwrap().unwrap();
+        assert_eq!(name, "set");
+        assert_eq!(
+            args,
+            Some(Token::Struct(vec![("value".to_owned(), Token::U64(5))]))
+        );
+
+        let (name, args) = abi.decode_call(1354761334, 1).unwrap().unwrap();
+        assert_eq!(name, "flag");
+        assert_eq!(
+            args,
+            Some(Token::Struct(vec![("on".to_owned(), Token::Bool(true))]))
+        );
+        assert!(abi.decode_call(1354761334, 2).is_err());
+
+        // structs are passed by reference
+        let emit = abi.functions.values().find(|f| f.name == "emit").unwrap();
+        let (name, args) = abi.decode_call(emit.selector(), 0x1000).unwrap().unwrap();
+        assert_eq!(name, "emit");
+        assert_eq!(args, None);
+
+        assert!(abi.decode_call(1, 5).unwrap().is_none());
+    }
+
+    #[test]
+    fn v1_abi_call_is_rejected() {
+        let json = r#"{
+            "specVersion": "1",
+            "encodingVersion": "1",
+            "concreteTypes": [{"type": "u64", "concreteTypeId": "u64"}]
+        }"#;
+        let abi = ContractAbi::parse(json).unwrap();
+
+        assert!(abi.decode_call(2247002193, 5).is_err());
+    }
+
+    #[test]
+    fn binary_tokens_to_python() {
+        let token = Token::Tuple(vec![
+            Token::B256([1; 32]),
+            Token::Struct(vec![(
+                "data".to_owned(),
+                Token::List(vec![Token::Bytes(vec![0xab, 0xcd])]),
+            )]),
+        ]);
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let hex = token.to_object(py, false).unwrap();
+            let (id, data): (String, HashMap<String, Vec<String>>) = hex.extract(py).unwrap();
+            assert_eq!(id, format!("0x{}", "01".repeat(32)));
+            assert_eq!(data["data"], vec!["0xabcd".to_owned()]);
+
+            let native = token.to_object(py, true).unwrap();
+            let (id, data): (&PyBytes, HashMap<String, Vec<&PyBytes>>) =
+                native.extract(py).unwrap();
+            assert_eq!(id.as_bytes(), &[1; 32]);
+            assert_eq!(data["data"][0].as_bytes(), &[0xab, 0xcd]);
+        });
+    }
+}
@@ -0,0 +1,379 @@
+use std::future::Future;
+
+use pyo3::prelude::*;
+
+use crate::column_mapping::ColumnMapping;
+use crate::export::ExportSummary;
+use crate::metrics::ClientStats;
+use crate::response::{LogResponse, QueryResponseArrow, QueryResponseTyped};
+use crate::stream::{ArrowStream, LogStream, QueryResponseStream};
+use crate::text_out::TextFormat;
+use crate::{
+    query, warn_deprecated, Config, HyperfuelClient, IpcConfig, ParquetConfig, StreamConfig,
+    TableFormat, TextConfig,
+};
+
+/// Blocking version of HyperfuelClient.
+///
+/// Runs requests on the internal tokio runtime and releases the GIL while waiting for them,
+/// so it can be used from plain scripts and notebooks without an asyncio event loop.
+#[pyclass]
+#[derive(Clone)]
+pub struct HyperfuelClientSync {
+    inner: HyperfuelClient,
+}
+
+/// Run the future to completion on the tokio runtime without holding the GIL.
+pub(crate) fn block_on<T, F>(py: Python, fut: F) -> PyResult<T>
+where
+    T: Send,
+    F: Future<Output = PyResult<T>> + Send,
+{
+    py.allow_threads(|| pyo3_asyncio::tokio::get_runtime().block_on(fut))
+}
+
+#[pymethods]
+impl HyperfuelClientSync {
+    /// Create a new client with given config
+    #[new]
+    fn new(config: Config) -> PyResult<HyperfuelClientSync> {
+        Ok(HyperfuelClientSync {
+            inner: HyperfuelClient::new(config)?,
+        })
+    }
+
+    /// Get the request metrics of the client.
+    ///
+    /// Same as HyperfuelClient.stats.
+    pub fn stats(&self) -> ClientStats {
+        self.inner.stats()
+    }
+
+    /// Get the height of the source hypersync instance
+    pub fn get_height(&self, py: Python) -> PyResult<u64> {
+        block_on(py, self.inner.clone().get_height_impl())
+    }
+
+    /// Get the height of the source hypersync instance
+    ///
+    /// Deprecated, emits a DeprecationWarning. Use get_height instead.
+    pub fn get_height_with_retry(&self, py: Python) -> PyResult<u64> {
+        warn_deprecated(py, "get_height_with_retry", "get_height")?;
+        block_on(py, self.inner.clone().get_height_impl())
+    }
+
+    /// Create a parquet file by executing a query.
+    ///
+    /// Same as HyperfuelClient.create_parquet_folder but blocks until the folder is written.
+    pub fn create_parquet_folder(
+        &self,
+        query: query::Query,
+        path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+        py: Python,
+    ) -> PyResult<ExportSummary> {
+        block_on(
+            py,
+            self.inner.clone().create_parquet_folder_impl(
+                query,
+                path,
+                column_mapping,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Run a query over its whole block range with several workers in parallel.
+    ///
+    /// Same as HyperfuelClient.parallel_collect but blocks until all chunks are fetched.
+    #[allow(clippy::too_many_arguments)]
+    pub fn parallel_collect(
+        &self,
+        query: query::Query,
+        num_workers: Option<usize>,
+        chunk_blocks: Option<u64>,
+        column_mapping: Option<ColumnMapping>,
+        path: Option<String>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+        py: Python,
+    ) -> PyResult<PyObject> {
+        block_on(
+            py,
+            self.inner.clone().parallel_collect_impl(
+                query,
+                num_workers,
+                chunk_blocks,
+                column_mapping,
+                path,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Create a folder of Arrow IPC files by executing a query.
+    ///
+    /// Same as HyperfuelClient.create_arrow_folder but blocks until the folder is written.
+    pub fn create_arrow_folder(
+        &self,
+        query: query::Query,
+        path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<IpcConfig>,
+        progress: Option<PyObject>,
+        py: Python,
+    ) -> PyResult<ExportSummary> {
+        block_on(
+            py,
+            self.inner.clone().create_arrow_folder_impl(
+                query,
+                path,
+                column_mapping,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response in which every table is bytes in the Arrow IPC streaming format.
+    pub fn get_arrow_ipc(
+        &self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python,
+    ) -> PyResult<QueryResponseArrow> {
+        block_on(
+            py,
+            self.inner.clone().get_arrow_ipc_impl(query, column_mapping),
+        )
+    }
+
+    /// Create a folder of CSV files by executing a query.
+    ///
+    /// Same as HyperfuelClient.create_csv_folder but blocks until the folder is written.
+    pub fn create_csv_folder(
+        &self,
+        query: query::Query,
+        path: String,
+        config: Option<TextConfig>,
+        progress: Option<PyObject>,
+        py: Python,
+    ) -> PyResult<ExportSummary> {
+        block_on(
+            py,
+            self.inner.clone().create_text_folder_impl(
+                query,
+                path,
+                TextFormat::Csv,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Create a folder of newline delimited JSON files by executing a query.
+    ///
+    /// Same as HyperfuelClient.create_ndjson_folder but blocks until the folder is written.
+    pub fn create_ndjson_folder(
+        &self,
+        query: query::Query,
+        path: String,
+        config: Option<TextConfig>,
+        progress: Option<PyObject>,
+        py: Python,
+    ) -> PyResult<ExportSummary> {
+        block_on(
+            py,
+            self.inner.clone().create_text_folder_impl(
+                query,
+                path,
+                TextFormat::Ndjson,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response which contains typed data.
+    pub fn get_data(&self, query: query::Query, py: Python) -> PyResult<QueryResponseTyped> {
+        block_on(py, self.inner.clone().get_data_impl(query))
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response that which contains structured data that doesn't include any inputs, outputs,
+    /// and receipts that don't exactly match the query's input, outout, or receipt selection.
+    pub fn get_selected_data(
+        &self,
+        query: query::Query,
+        py: Python,
+    ) -> PyResult<QueryResponseTyped> {
+        block_on(py, self.inner.clone().get_selected_data_impl(query))
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns all log and logdata receipts of logs emitted by any of the specified contracts
+    /// within the block range.
+    ///
+    /// NOTE: this function is experimental and might be removed in future versions.
+    pub fn preset_query_get_logs(
+        &self,
+        emitting_contracts: Vec<String>,
+        from_block: u64,
+        to_block: Option<u64>,
+        py: Python,
+    ) -> PyResult<LogResponse> {
+        block_on(
+            py,
+            self.inner
+                .clone()
+                .preset_query_get_logs_impl(emitting_contracts, from_block, to_block),
+        )
+    }
+
+    /// Send a log query request to the source hypersync instance.
+    pub fn get_logs(&self, query: query::LogQuery, py: Python) -> PyResult<LogResponse> {
+        block_on(py, self.inner.clone().get_logs_impl(query))
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response which contains pyarrow data.
+    pub fn get_arrow_data(
+        &self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python,
+    ) -> PyResult<QueryResponseArrow> {
+        block_on(
+            py,
+            self.inner
+                .clone()
+                .get_arrow_data_impl(query, TableFormat::Pyarrow, column_mapping),
+        )
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Deprecated, emits a DeprecationWarning. Use get_arrow_data instead.
+    pub fn get_arrow_data_with_retry(
+        &self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python,
+    ) -> PyResult<QueryResponseArrow> {
+        warn_deprecated(py, "get_arrow_data_with_retry", "get_arrow_data")?;
+        block_on(
+            py,
+            self.inner
+                .clone()
+                .get_arrow_data_impl(query, TableFormat::Pyarrow, column_mapping),
+        )
+    }
+
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response which contains polars DataFrames.
+    pub fn get_polars_data(
+        &self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python,
+    ) -> PyResult<QueryResponseArrow> {
+        block_on(
+            py,
+            self.inner
+                .clone()
+                .get_arrow_data_impl(query, TableFormat::Polars, column_mapping),
+        )
+    }
+
+    /// Stream the results of a query, page by page.
+    ///
+    /// Returns an iterator that yields typed pages.
+    pub fn stream(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+    ) -> PyResult<QueryResponseStream> {
+        self.inner.stream(query, config)
+    }
+
+    /// Stream the results of a query, page by page.
+    ///
+    /// Same as stream but yields pages that contain pyarrow data.
+    pub fn stream_arrow(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        self.inner.stream_arrow(query, config, column_mapping)
+    }
+
+    /// Stream the results of a query, page by page.
+    ///
+    /// Same as stream but yields pages that contain polars DataFrames.
+    pub fn stream_polars(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        self.inner.stream_polars(query, config, column_mapping)
+    }
+
+    /// Stream the results of a log query, page by page.
+    ///
+    /// Same as stream but yields log response pages.
+    pub fn stream_logs(
+        &self,
+        query: query::LogQuery,
+        config: Option<StreamConfig>,
+    ) -> PyResult<LogStream> {
+        self.inner.stream_logs(query, config)
+    }
+
+    /// Stream the results of a query and keep following the head of the chain.
+    ///
+    /// Returns an iterator that yields typed pages.
+    pub fn tail(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+    ) -> PyResult<QueryResponseStream> {
+        self.inner.tail(query, config)
+    }
+
+    /// Stream the results of a query and keep following the head of the chain.
+    ///
+    /// Same as tail but yields pages that contain pyarrow data.
+    pub fn tail_arrow(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        self.inner.tail_arrow(query, config, column_mapping)
+    }
+
+    /// Stream the results of a log query and keep following the head of the chain.
+    ///
+    /// Same as tail but yields log response pages.
+    pub fn tail_logs(
+        &self,
+        query: query::LogQuery,
+        config: Option<StreamConfig>,
+    ) -> PyResult<LogStream> {
+        self.inner.tail_logs(query, config)
+    }
+}
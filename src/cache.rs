@@ -0,0 +1,329 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use arrow2::{
+    datatypes::Schema,
+    io::ipc::{
+        read::{read_stream_metadata, StreamReader, StreamState},
+        write::{StreamWriter, WriteOptions},
+    },
+};
+use hyperfuel_client::{ArrowBatch, QueryResponse, QueryResponseData};
+use hyperfuel_net_types::Query;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+
+/// Bumped when the layout of the cache entries changes, so old entries are not read.
+const CACHE_VERSION: &str = "v1";
+
+const TABLES: [&str; 5] = ["block", "transaction", "receipt", "input", "output"];
+
+/// On-disk cache of arrow query responses.
+///
+/// Entries are keyed by the hash of the query JSON, which includes the block range, and hold the
+/// tables of the response as Arrow IPC streams. Only responses that end below the archive height
+/// of the source are stored, so a cached page never misses blocks that weren't finalized yet.
+///
+/// Only the arrow requests of the client go through the cache, typed data, logs and the height are
+/// always fetched from the source. A cached response keeps the archive height of the source at the
+/// time it was stored. Entries are never evicted, the folder grows with every distinct query until
+/// it is removed.
+pub struct QueryCache {
+    dir: PathBuf,
+}
+
+/// Fields of a cached response besides its tables.
+#[derive(Serialize, Deserialize)]
+struct CacheMeta {
+    archive_height: Option<u64>,
+    next_block: u64,
+    total_execution_time: u64,
+}
+
+impl QueryCache {
+    pub fn from_config(cfg: &Config) -> Result<Option<Self>> {
+        let dir = match &cfg.cache_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => return Ok(None),
+        };
+
+        fs::create_dir_all(&dir).context("create cache dir")?;
+
+        Ok(Some(Self { dir }))
+    }
+
+    fn entry_dir(&self, query: &Query) -> Result<PathBuf> {
+        let query = serde_json::to_vec(query).context("serialize query")?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_VERSION.as_bytes());
+        hasher.update(&query);
+        let key = faster_hex::hex_string(&hasher.finalize());
+
+        Ok(self.dir.join(key))
+    }
+
+    /// Returns the cached response of the query, if there is one.
+    pub async fn get(&self, query: &Query) -> Result<Option<QueryResponse>> {
+        let dir = self.entry_dir(query)?;
+
+        tokio::task::spawn_blocking(move || {
+            if !dir.exists() {
+                return Ok(None);
+            }
+            read_entry(&dir).map(Some)
+        })
+        .await
+        .context("read cache entry")?
+    }
+
+    /// Stores the response of the query if all the blocks it covers are below the archive height.
+    ///
+    /// An existing entry of the query is replaced, so an entry that can't be read is repaired
+    /// by the next response of its query.
+    pub async fn put(&self, query: &Query, res: &QueryResponse) -> Result<()> {
+        match res.archive_height {
+            Some(archive_height) if res.next_block <= archive_height => (),
+            _ => return Ok(()),
+        }
+
+        let dir = self.entry_dir(query)?;
+        let tmp = self.temp_dir("tmp");
+        let old = self.temp_dir("old");
+        let res = res.clone();
+
+        tokio::task::spawn_blocking(move || {
+            write_entry(&tmp, &res)?;
+            commit_entry(&tmp, &old, &dir)
+        })
+        .await
+        .context("write cache entry")?
+    }
+
+    /// A unique path in the cache folder that is never read as an entry.
+    fn temp_dir(&self, prefix: &str) -> PathBuf {
+        self.dir
+            .join(format!(".{}-{:016x}", prefix, rand::random::<u64>()))
+    }
+}
+
+/// Moves the entry written to `tmp` to `dir`.
+///
+/// An existing entry is moved aside to `old` first and removed once the new one is in place,
+/// so readers see either a complete entry or none, never a partially removed one.
+fn commit_entry(tmp: &Path, old: &Path, dir: &Path) -> Result<()> {
+    match fs::rename(dir, old) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+        Err(e) => return Err(anyhow::Error::new(e).context("move previous cache entry aside")),
+    }
+
+    if let Err(e) = fs::rename(tmp, dir) {
+        fs::remove_dir_all(tmp).context("remove temporary cache entry")?;
+        // another request might have committed the same entry in the meantime
+        if !dir.exists() {
+            return Err(anyhow::Error::new(e).context("commit cache entry"));
+        }
+    }
+
+    match fs::remove_dir_all(old) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(anyhow::Error::new(e).context("remove previous cache entry"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn write_entry(dir: &Path, res: &QueryResponse) -> Result<()> {
+    fs::create_dir_all(dir).context("create cache entry")?;
+
+    let meta = CacheMeta {
+        archive_height: res.archive_height,
+        next_block: res.next_block,
+        total_execution_time: res.total_execution_time,
+    };
+    fs::write(
+        dir.join("meta.json"),
+        serde_json::to_vec(&meta).context("serialize meta")?,
+    )
+    .context("write meta")?;
+
+    let data = &res.data;
+    let tables = [
+        &data.blocks,
+        &data.transactions,
+        &data.receipts,
+        &data.inputs,
+        &data.outputs,
+    ];
+    for (table, batches) in TABLES.into_iter().zip(tables) {
+        let schema = batches
+            .first()
+            .map(|b| b.schema.as_ref().clone())
+            .unwrap_or_else(Schema::default);
+
+        let mut writer = StreamWriter::new(Vec::new(), WriteOptions { compression: None });
+        writer.start(&schema, None)?;
+        for batch in batches {
+            writer.write(&batch.chunk, None)?;
+        }
+        writer.finish()?;
+
+        fs::write(dir.join(format!("{}.arrows", table)), writer.into_inner())
+            .with_context(|| format!("write {} table", table))?;
+    }
+
+    Ok(())
+}
+
+fn read_entry(dir: &Path) -> Result<QueryResponse> {
+    let meta = fs::read(dir.join("meta.json")).context("read meta")?;
+    let meta: CacheMeta = serde_json::from_slice(&meta).context("parse meta")?;
+
+    let mut tables = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        let data = fs::read(dir.join(format!("{}.arrows", table)))
+            .with_context(|| format!("read {} table", table))?;
+        tables.push(read_batches(data).with_context(|| format!("decode {} table", table))?);
+    }
+    let [blocks, transactions, receipts, inputs, outputs]: [Vec<ArrowBatch>; 5] = tables
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("unexpected number of tables"))?;
+
+    Ok(QueryResponse {
+        archive_height: meta.archive_height,
+        next_block: meta.next_block,
+        total_execution_time: meta.total_execution_time,
+        data: QueryResponseData {
+            blocks,
+            transactions,
+            receipts,
+            inputs,
+            outputs,
+        },
+    })
+}
+
+fn read_batches(data: Vec<u8>) -> Result<Vec<ArrowBatch>> {
+    let mut reader = Cursor::new(data);
+    let metadata = read_stream_metadata(&mut reader)?;
+    let schema = Arc::new(metadata.schema.clone());
+
+    let mut batches = Vec::new();
+    for state in StreamReader::new(reader, metadata, None) {
+        match state? {
+            StreamState::Some(chunk) => batches.push(ArrowBatch {
+                chunk,
+                schema: schema.clone(),
+            }),
+            StreamState::Waiting => break,
+        }
+    }
+
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow2::{
+        array::UInt64Array,
+        chunk::Chunk,
+        datatypes::{DataType, Field},
+    };
+
+    use super::*;
+
+    fn temp_cache() -> QueryCache {
+        let dir =
+            std::env::temp_dir().join(format!("hyperfuel-cache-{:016x}", rand::random::<u64>()));
+        fs::create_dir_all(&dir).unwrap();
+        QueryCache { dir }
+    }
+
+    fn query(from_block: u64, to_block: u64) -> Query {
+        Query {
+            from_block,
+            to_block: Some(to_block),
+            ..Default::default()
+        }
+    }
+
+    fn response(next_block: u64, archive_height: Option<u64>) -> QueryResponse {
+        let schema = Arc::new(Schema::from(vec![Field::new(
+            "height",
+            DataType::UInt64,
+            false,
+        )]));
+        let chunk = Chunk::new(vec![UInt64Array::from_vec(vec![1, 2]).boxed()]);
+
+        QueryResponse {
+            archive_height,
+            next_block,
+            total_execution_time: 7,
+            data: QueryResponseData {
+                blocks: vec![ArrowBatch { chunk, schema }],
+                transactions: Vec::new(),
+                receipts: Vec::new(),
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let cache = temp_cache();
+        let query = query(0, 10);
+
+        assert!(cache.get(&query).await.unwrap().is_none());
+        cache.put(&query, &response(10, Some(20))).await.unwrap();
+
+        let res = cache.get(&query).await.unwrap().unwrap();
+        assert_eq!(res.archive_height, Some(20));
+        assert_eq!(res.next_block, 10);
+        assert_eq!(res.total_execution_time, 7);
+        assert_eq!(res.data.blocks.len(), 1);
+        assert_eq!(res.data.blocks[0].chunk.len(), 2);
+        assert!(res.data.receipts.is_empty());
+
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn skip_unfinalized() {
+        let cache = temp_cache();
+        let query = query(0, 30);
+
+        cache.put(&query, &response(21, Some(20))).await.unwrap();
+        cache.put(&query, &response(21, None)).await.unwrap();
+        assert!(cache.get(&query).await.unwrap().is_none());
+
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn repair_broken_entry() {
+        let cache = temp_cache();
+        let query = query(0, 10);
+
+        let dir = cache.entry_dir(&query).unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("meta.json"), b"{").unwrap();
+        assert!(cache.get(&query).await.is_err());
+
+        cache.put(&query, &response(10, Some(20))).await.unwrap();
+        assert_eq!(cache.get(&query).await.unwrap().unwrap().next_block, 10);
+
+        // only the entry is left in the cache folder
+        assert_eq!(fs::read_dir(&cache.dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use arrow2::chunk::Chunk;
+use arrow2::compute::cast::{can_cast_types, cast, CastOptions};
+use arrow2::datatypes::{DataType, Field, PhysicalType, Schema, TimeUnit};
+use hyperfuel_client::ArrowBatch;
+
+use crate::query::ValidationError;
+
+/// Target types of columns in the arrow data, per table. Keys are column names and values
+/// are one of "uint64", "uint32", "int64", "int32", "float32", "float64" or "timestamp".
+#[derive(Default, Clone, Debug, dict_derive::FromPyObject)]
+pub struct ColumnMapping {
+    pub block: Option<BTreeMap<String, String>>,
+    pub transaction: Option<BTreeMap<String, String>>,
+    pub receipt: Option<BTreeMap<String, String>>,
+    pub input: Option<BTreeMap<String, String>>,
+    pub output: Option<BTreeMap<String, String>>,
+}
+
+impl ColumnMapping {
+    /// Checks that every mapped column exists and can be cast to the requested type.
+    pub fn try_convert(&self) -> Result<Mapping> {
+        Ok(Mapping {
+            block: parse_table("block", &self.block, &hyperfuel_schema::block_header())?,
+            transaction: parse_table(
+                "transaction",
+                &self.transaction,
+                &hyperfuel_schema::transaction(),
+            )?,
+            receipt: parse_table("receipt", &self.receipt, &hyperfuel_schema::receipt())?,
+            input: parse_table("input", &self.input, &hyperfuel_schema::input())?,
+            output: parse_table("output", &self.output, &hyperfuel_schema::output())?,
+        })
+    }
+}
+
+/// Parsed column mapping, an empty mapping leaves the data as it is.
+#[derive(Default, Clone, Debug)]
+pub struct Mapping {
+    pub block: TableMapping,
+    pub transaction: TableMapping,
+    pub receipt: TableMapping,
+    pub input: TableMapping,
+    pub output: TableMapping,
+}
+
+/// Target types of the mapped columns, per table and column.
+pub type ColumnTypes = BTreeMap<String, BTreeMap<String, String>>;
+
+impl Mapping {
+    /// Target types of the mapped columns, tables without mapped columns are left out.
+    pub fn column_types(&self) -> ColumnTypes {
+        [
+            ("block", &self.block),
+            ("transaction", &self.transaction),
+            ("receipt", &self.receipt),
+            ("input", &self.input),
+            ("output", &self.output),
+        ]
+        .into_iter()
+        .filter(|(_, mapping)| !mapping.0.is_empty())
+        .map(|(table, mapping)| {
+            let columns = mapping
+                .0
+                .iter()
+                .map(|(column, data_type)| (column.clone(), format!("{:?}", data_type)))
+                .collect();
+            (table.to_owned(), columns)
+        })
+        .collect()
+    }
+}
+
+/// Target types of the mapped columns of a single table.
+#[derive(Default, Clone, Debug)]
+pub struct TableMapping(BTreeMap<String, DataType>);
+
+impl TableMapping {
+    /// Schema of the table after casting.
+    pub fn map_schema(&self, schema: &Schema) -> Schema {
+        if self.0.is_empty() {
+            return schema.clone();
+        }
+
+        let fields: Vec<Field> = schema.fields.iter().map(|f| self.map_field(f)).collect();
+        Schema::from(fields).with_metadata(schema.metadata.clone())
+    }
+
+    fn map_field(&self, field: &Field) -> Field {
+        match self.0.get(&field.name) {
+            Some(data_type) => Field::new(field.name.clone(), data_type.clone(), field.is_nullable),
+            None => field.clone(),
+        }
+    }
+
+    /// Casts the mapped columns of a chunk, the schema describes the columns of the chunk.
+    pub fn cast_chunk(
+        &self,
+        schema: &Schema,
+        chunk: Chunk<Box<dyn arrow2::array::Array>>,
+    ) -> Result<Chunk<Box<dyn arrow2::array::Array>>> {
+        if self.0.is_empty() {
+            return Ok(chunk);
+        }
+
+        let arrays = chunk
+            .into_arrays()
+            .into_iter()
+            .zip(schema.fields.iter())
+            .map(|(array, field)| match self.0.get(&field.name) {
+                Some(data_type) => cast(array.as_ref(), data_type, CastOptions::default())
+                    .with_context(|| format!("cast column {} to {:?}", field.name, data_type)),
+                None => Ok(array),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Chunk::try_new(arrays).context("build casted chunk")
+    }
+
+    /// Casts the mapped columns of all batches.
+    pub fn cast_batches(&self, batches: Vec<ArrowBatch>) -> Result<Vec<ArrowBatch>> {
+        if self.0.is_empty() {
+            return Ok(batches);
+        }
+
+        batches
+            .into_iter()
+            .map(|batch| {
+                let chunk = self.cast_chunk(&batch.schema, batch.chunk)?;
+                let schema = Arc::new(self.map_schema(&batch.schema));
+                Ok(ArrowBatch { chunk, schema })
+            })
+            .collect()
+    }
+}
+
+fn parse_table(
+    table: &str,
+    columns: &Option<BTreeMap<String, String>>,
+    schema: &Schema,
+) -> Result<TableMapping, ValidationError> {
+    let mut mapping = BTreeMap::new();
+
+    for (column, data_type) in columns.iter().flatten() {
+        let path = format!("column_mapping.{}.{}", table, column);
+
+        let field = schema
+            .fields
+            .iter()
+            .find(|f| &f.name == column)
+            .ok_or_else(|| {
+                ValidationError::new(&path, format!("unknown {} column \"{}\"", table, column))
+            })?;
+        let data_type =
+            parse_data_type(data_type).map_err(|e| ValidationError::new(&path, e.to_string()))?;
+        let is_numeric = matches!(
+            field.data_type.to_physical_type(),
+            PhysicalType::Primitive(_)
+        );
+        if !is_numeric || !can_cast_types(&field.data_type, &data_type) {
+            return Err(ValidationError::new(
+                &path,
+                format!("can't cast {:?} to {:?}", field.data_type, data_type),
+            ));
+        }
+
+        mapping.insert(column.clone(), data_type);
+    }
+
+    Ok(TableMapping(mapping))
+}
+
+fn parse_data_type(name: &str) -> Result<DataType> {
+    match name {
+        "uint64" => Ok(DataType::UInt64),
+        "uint32" => Ok(DataType::UInt32),
+        "int64" => Ok(DataType::Int64),
+        "int32" => Ok(DataType::Int32),
+        "float32" => Ok(DataType::Float32),
+        "float64" => Ok(DataType::Float64),
+        "timestamp" => Ok(DataType::Timestamp(
+            TimeUnit::Second,
+            Some("UTC".to_owned()),
+        )),
+        _ => Err(anyhow!("unknown data type \"{}\"", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow2::array::{Float64Array, UInt64Array};
+
+    use super::*;
+
+    fn mapping(table: &str, column: &str, data_type: &str) -> ColumnMapping {
+        let columns = Some(BTreeMap::from([(column.to_owned(), data_type.to_owned())]));
+        match table {
+            "block" => ColumnMapping {
+                block: columns,
+                ..Default::default()
+            },
+            "receipt" => ColumnMapping {
+                receipt: columns,
+                ..Default::default()
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn numeric_column() {
+        let mapping = mapping("block", "height", "float64").try_convert().unwrap();
+
+        let schema = Schema::from(vec![Field::new("height", DataType::UInt64, false)]);
+        assert_eq!(
+            mapping.block.map_schema(&schema).fields[0].data_type,
+            DataType::Float64
+        );
+
+        let chunk = Chunk::new(vec![UInt64Array::from_slice([1, 2]).boxed()]);
+        let chunk = mapping.block.cast_chunk(&schema, chunk).unwrap();
+        let casted = chunk.arrays()[0]
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(casted.values().as_slice(), &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn mapped_column_types() {
+        assert!(Mapping::default().column_types().is_empty());
+
+        let mapping = mapping("receipt", "amount", "int64").try_convert().unwrap();
+        assert_eq!(
+            mapping.column_types(),
+            ColumnTypes::from([(
+                "receipt".to_owned(),
+                BTreeMap::from([("amount".to_owned(), "Int64".to_owned())])
+            )])
+        );
+    }
+
+    #[test]
+    fn timestamp_column() {
+        let mapping = mapping("block", "time", "timestamp").try_convert().unwrap();
+        assert!(matches!(
+            mapping.block.0.get("time"),
+            Some(DataType::Timestamp(TimeUnit::Second, _))
+        ));
+    }
+
+    #[test]
+    fn unknown_column() {
+        let err = mapping("block", "nope", "int64").try_convert().unwrap_err();
+        let err = err.downcast::<ValidationError>().unwrap();
+        assert_eq!(err.field, "column_mapping.block.nope");
+    }
+
+    #[test]
+    fn unknown_data_type() {
+        let err = mapping("block", "height", "uint128")
+            .try_convert()
+            .unwrap_err();
+        let err = err.downcast::<ValidationError>().unwrap();
+        assert_eq!(err.field, "column_mapping.block.height");
+    }
+
+    #[test]
+    fn binary_column_is_rejected() {
+        let err = mapping("receipt", "tx_id", "uint64")
+            .try_convert()
+            .unwrap_err();
+        let err = err.downcast::<ValidationError>().unwrap();
+        assert_eq!(err.field, "column_mapping.receipt.tx_id");
+        assert!(err.msg.starts_with("can't cast"));
+    }
+
+    #[test]
+    fn empty_mapping_keeps_schema() {
+        let mapping = ColumnMapping::default().try_convert().unwrap();
+        let schema = hyperfuel_schema::receipt();
+        assert_eq!(mapping.receipt.map_schema(&schema), *schema);
+    }
+}
@@ -1,6 +1,11 @@
-use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use arrow2::io::parquet::write::CompressionOptions;
 use serde::Serialize;
 
+use crate::query::ValidationError;
+
 #[derive(Default, Clone, Serialize, dict_derive::FromPyObject)]
 pub struct Config {
     /// Url of the source hypersync instance
@@ -11,6 +16,44 @@ pub struct Config {
     /// Timout treshold for a single http request in milliseconds, default is 30 seconds (30_000ms)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http_req_timeout_millis: Option<i64>,
+    /// Maximum number of retries for a failed request, default is 12
+    #[serde(skip)]
+    pub max_num_retries: Option<usize>,
+    /// Delay before the first retry in milliseconds, doubled on each following retry, default is 500ms
+    #[serde(skip)]
+    pub retry_base_ms: Option<u64>,
+    /// Maximum delay between retries in milliseconds, default is 5 seconds (5_000ms)
+    #[serde(skip)]
+    pub retry_ceiling_ms: Option<u64>,
+    /// Error classes that are retried, one of "timeout", "connection", "rate_limit", "auth",
+    /// "server", "http", "decode" or "other".
+    /// Default is ["timeout", "connection", "rate_limit", "server"]
+    #[serde(skip)]
+    pub retry_on: Option<Vec<String>>,
+    /// Maximum number of requests of the client that are in flight at the same time, shared by
+    /// all of its methods, streams and parallel fetches. Default is no limit.
+    #[serde(skip)]
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum average number of requests per second the client sends, shared by all of its
+    /// methods, including retries. Bursts of up to a second worth of requests are allowed.
+    /// Default is no limit.
+    #[serde(skip)]
+    pub requests_per_second: Option<f64>,
+    /// Sway JSON ABIs keyed by contract id, LogData receipts emitted by these contracts
+    /// are decoded into the `decoded` field of the logs.
+    #[serde(skip)]
+    pub abis: Option<HashMap<String, String>>,
+    /// Return binary fields of typed and log responses as bytes and quantities as ints instead of
+    /// hex strings, b256 and bytes values decoded with the `abis` too. Default is false
+    #[serde(skip)]
+    pub native_types: Option<bool>,
+    /// Folder of an on-disk cache for the arrow responses of the client. Pages that end below the
+    /// archive height of the source are stored keyed by their query and block range, and identical
+    /// queries are served from the folder afterwards. Cached pages keep the archive height they were
+    /// fetched with and the folder has no size limit, entries stay until it is removed.
+    /// Default is to not cache responses.
+    #[serde(skip)]
+    pub cache_dir: Option<String>,
 }
 
 impl Config {
@@ -19,3 +62,191 @@ impl Config {
         serde_json::from_slice(&json).context("parse json")
     }
 }
+
+#[derive(Default, Clone, dict_derive::FromPyObject)]
+pub struct StreamConfig {
+    /// Number of block ranges to fetch in parallel, default is 4
+    pub concurrency: Option<usize>,
+    /// Number of blocks in each block range that is fetched in parallel, default is 100_000
+    pub batch_size: Option<u64>,
+    /// Number of pages that can be buffered before fetching is paused until the
+    /// consumer catches up, default is 16
+    pub buffer_size: Option<usize>,
+    /// Interval in milliseconds for polling the height of the source once the
+    /// head of the chain is reached in tail mode, default is 1 second (1_000ms)
+    pub poll_interval_millis: Option<u64>,
+}
+
+#[derive(Default, Clone, dict_derive::FromPyObject)]
+pub struct ParquetConfig {
+    /// Number of blocks after which the files that are being written are finished and
+    /// committed to the checkpoint, the following blocks are written to a new part.
+    /// An interrupted export continues from the last commit. Default is 1_000_000 blocks.
+    pub checkpoint_interval_blocks: Option<u64>,
+    /// Continue from the checkpoint of a previous run of the same query in the output folder,
+    /// default is true. If false, the previous output is replaced.
+    pub resume: Option<bool>,
+    /// Write Hive style partitions of this many blocks per table instead of a file per table,
+    /// e.g. 'receipts/block_bucket=1000000/part-0.parquet'.
+    /// Default is to not partition the output.
+    pub partition_blocks: Option<u64>,
+    /// Number of rows of a table after which its file is finished and the following rows
+    /// are written to a new part. Files can exceed it by the rows of a single response.
+    /// Default is no limit.
+    pub max_rows_per_file: Option<u64>,
+    /// Compression codec of the parquet files, one of "lz4_raw", "lz4", "zstd", "gzip" or
+    /// "uncompressed". Default is "lz4_raw".
+    pub compression: Option<String>,
+}
+
+const DEFAULT_CHECKPOINT_INTERVAL_BLOCKS: u64 = 1_000_000;
+
+/// Parsed options of the export driver, shared by all output formats.
+#[derive(Clone, Copy, Debug)]
+pub struct ExportOptions {
+    pub checkpoint_interval_blocks: u64,
+    pub resume: bool,
+    pub partition_blocks: Option<u64>,
+    pub max_rows_per_file: u64,
+}
+
+impl ExportOptions {
+    fn new(
+        checkpoint_interval_blocks: Option<u64>,
+        resume: Option<bool>,
+        partition_blocks: Option<u64>,
+        max_rows_per_file: Option<u64>,
+    ) -> Result<Self, ValidationError> {
+        let positive = |name: &str, value: Option<u64>| match value {
+            Some(0) => Err(ValidationError::new(
+                format!("config.{}", name),
+                "must be greater than zero",
+            )),
+            value => Ok(value),
+        };
+
+        Ok(Self {
+            checkpoint_interval_blocks: positive(
+                "checkpoint_interval_blocks",
+                checkpoint_interval_blocks,
+            )?
+            .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL_BLOCKS),
+            resume: resume.unwrap_or(true),
+            partition_blocks: positive("partition_blocks", partition_blocks)?,
+            max_rows_per_file: positive("max_rows_per_file", max_rows_per_file)?
+                .unwrap_or(u64::MAX),
+        })
+    }
+}
+
+/// Parsed parquet config.
+#[derive(Clone, Copy, Debug)]
+pub struct ParquetOptions {
+    pub export: ExportOptions,
+    pub compression: CompressionOptions,
+}
+
+impl ParquetConfig {
+    pub fn try_convert(&self) -> Result<ParquetOptions, ValidationError> {
+        let compression = match &self.compression {
+            Some(name) => parse_compression(name)
+                .map_err(|e| ValidationError::new("config.compression", e.to_string()))?,
+            None => CompressionOptions::Lz4Raw,
+        };
+
+        Ok(ParquetOptions {
+            export: ExportOptions::new(
+                self.checkpoint_interval_blocks,
+                self.resume,
+                self.partition_blocks,
+                self.max_rows_per_file,
+            )?,
+            compression,
+        })
+    }
+}
+
+fn parse_compression(name: &str) -> Result<CompressionOptions> {
+    match name {
+        "lz4_raw" => Ok(CompressionOptions::Lz4Raw),
+        "lz4" => Ok(CompressionOptions::Lz4),
+        "zstd" => Ok(CompressionOptions::Zstd(None)),
+        "gzip" => Ok(CompressionOptions::Gzip(None)),
+        "uncompressed" => Ok(CompressionOptions::Uncompressed),
+        _ => Err(anyhow!("unknown compression \"{}\"", name)),
+    }
+}
+
+#[derive(Default, Clone, dict_derive::FromPyObject)]
+pub struct TextConfig {
+    /// Render amounts as 0x prefixed hex instead of decimal, default is false. Heights, indexes
+    /// and enum values like receipt_type or status stay decimal. Binary fields like ids are
+    /// always hex encoded.
+    pub hex_numbers: Option<bool>,
+    /// Same as in ParquetConfig
+    pub checkpoint_interval_blocks: Option<u64>,
+    /// Same as in ParquetConfig
+    pub resume: Option<bool>,
+}
+
+/// Parsed csv and ndjson config.
+#[derive(Clone, Copy, Debug)]
+pub struct TextOptions {
+    pub export: ExportOptions,
+    pub hex_numbers: bool,
+}
+
+impl TextConfig {
+    pub fn try_convert(&self) -> Result<TextOptions, ValidationError> {
+        Ok(TextOptions {
+            export: ExportOptions::new(self.checkpoint_interval_blocks, self.resume, None, None)?,
+            hex_numbers: self.hex_numbers.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Default, Clone, dict_derive::FromPyObject)]
+pub struct IpcConfig {
+    /// "file" for the Arrow IPC file format (Feather v2) that can be memory mapped, written to
+    /// '<table>.arrow', or "stream" for the IPC streaming format, written to '<table>.arrows'.
+    /// Default is "file".
+    pub format: Option<String>,
+    /// Same as in ParquetConfig
+    pub checkpoint_interval_blocks: Option<u64>,
+    /// Same as in ParquetConfig
+    pub resume: Option<bool>,
+}
+
+/// Arrow IPC format of an export.
+#[derive(Clone, Copy, Debug)]
+pub enum IpcFormat {
+    File,
+    Stream,
+}
+
+/// Parsed arrow IPC config.
+#[derive(Clone, Copy, Debug)]
+pub struct IpcOptions {
+    pub export: ExportOptions,
+    pub format: IpcFormat,
+}
+
+impl IpcConfig {
+    pub fn try_convert(&self) -> Result<IpcOptions, ValidationError> {
+        let format = match self.format.as_deref() {
+            None | Some("file") => IpcFormat::File,
+            Some("stream") => IpcFormat::Stream,
+            Some(format) => {
+                return Err(ValidationError::new(
+                    "config.format",
+                    format!("unknown ipc format \"{}\"", format),
+                ))
+            }
+        };
+
+        Ok(IpcOptions {
+            export: ExportOptions::new(self.checkpoint_interval_blocks, self.resume, None, None)?,
+            format,
+        })
+    }
+}
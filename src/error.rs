@@ -0,0 +1,324 @@
+use anyhow::{anyhow, Result};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyTimeoutError},
+    prelude::*,
+    sync::GILOnceCell,
+    types::{PyDict, PyTuple, PyType},
+};
+
+use crate::query::ValidationError;
+
+create_exception!(
+    hyperfuel.errors,
+    HyperfuelError,
+    PyException,
+    "Base class of all errors raised by the hyperfuel client."
+);
+create_exception!(
+    hyperfuel.errors,
+    HttpError,
+    HyperfuelError,
+    "The server responded with a non success status code."
+);
+create_exception!(
+    hyperfuel.errors,
+    AuthError,
+    HttpError,
+    "The server rejected the bearer token (401 or 403)."
+);
+create_exception!(
+    hyperfuel.errors,
+    RateLimitError,
+    HttpError,
+    "The server rejected the request because of too many requests (429)."
+);
+create_exception!(
+    hyperfuel.errors,
+    ServerError,
+    HttpError,
+    "The server failed to handle the request (5xx)."
+);
+create_exception!(
+    hyperfuel.errors,
+    DecodeError,
+    HyperfuelError,
+    "The response of the server couldn't be decoded."
+);
+create_exception!(
+    hyperfuel.errors,
+    QueryValidationError,
+    HyperfuelError,
+    "The query is invalid, the offending field is set on the error."
+);
+
+static TIMEOUT_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// The request to the server timed out.
+///
+/// Subclasses both HyperfuelError and the builtin TimeoutError, so it is caught by handlers
+/// of either. create_exception! only supports a single base class, so the class is created
+/// at runtime.
+fn timeout_error(py: Python<'_>) -> PyResult<&PyType> {
+    TIMEOUT_ERROR
+        .get_or_try_init(py, || {
+            let dict = PyDict::new(py);
+            dict.set_item("__module__", "hyperfuel.errors")?;
+            dict.set_item("__doc__", "The request to the server timed out.")?;
+
+            let bases = PyTuple::new(
+                py,
+                [
+                    py.get_type::<HyperfuelError>(),
+                    py.get_type::<PyTimeoutError>(),
+                ],
+            );
+            let class = py
+                .get_type::<PyType>()
+                .call1(("TimeoutError", bases, dict))?
+                .downcast::<PyType>()?;
+
+            Ok::<_, PyErr>(class.into())
+        })
+        .map(|class| class.as_ref(py))
+}
+
+/// Registers the exception classes on the python module.
+pub fn add_exceptions(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("HyperfuelError", py.get_type::<HyperfuelError>())?;
+    m.add("HttpError", py.get_type::<HttpError>())?;
+    m.add("AuthError", py.get_type::<AuthError>())?;
+    m.add("RateLimitError", py.get_type::<RateLimitError>())?;
+    m.add("ServerError", py.get_type::<ServerError>())?;
+    m.add("TimeoutError", timeout_error(py)?)?;
+    m.add("DecodeError", py.get_type::<DecodeError>())?;
+    m.add(
+        "QueryValidationError",
+        py.get_type::<QueryValidationError>(),
+    )?;
+    Ok(())
+}
+
+/// Converts an error into the matching python exception.
+///
+/// Every exception has a `kind` attribute with the error class (same names as `retry_on` in the config)
+/// and http errors additionally carry the `status_code` of the response.
+pub fn py_err(err: anyhow::Error) -> PyErr {
+    let kind = ErrorKind::of(&err);
+    let status = status_code(&err);
+    let msg = format!("{:?}", err);
+
+    Python::with_gil(|py| {
+        let py_err = match kind {
+            ErrorKind::Timeout => match timeout_error(py) {
+                Ok(class) => PyErr::from_type(class, msg),
+                Err(e) => e,
+            },
+            ErrorKind::Auth => AuthError::new_err(msg),
+            ErrorKind::RateLimit => RateLimitError::new_err(msg),
+            ErrorKind::Server => ServerError::new_err(msg),
+            ErrorKind::Http => HttpError::new_err(msg),
+            ErrorKind::Decode => DecodeError::new_err(msg),
+            ErrorKind::Connection | ErrorKind::Other => HyperfuelError::new_err(msg),
+        };
+
+        let value = py_err.value(py);
+        value.setattr("kind", kind.as_str()).ok();
+        value.setattr("status_code", status).ok();
+
+        py_err
+    })
+}
+
+/// Creates a DecodeError for a response that was received but couldn't be converted.
+pub fn decode_err(err: anyhow::Error) -> PyErr {
+    let py_err = DecodeError::new_err(format!("{:?}", err));
+
+    Python::with_gil(|py| {
+        let value = py_err.value(py);
+        value.setattr("kind", ErrorKind::Decode.as_str()).ok();
+        value.setattr("status_code", None::<u16>).ok();
+    });
+
+    py_err
+}
+
+/// Creates a QueryValidationError, the `field` attribute is set to the path of the offending value
+/// in the query (e.g. `receipts[2].asset_id[0]`) if the error was found by query validation.
+pub fn validation_err(err: anyhow::Error) -> PyErr {
+    let field = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ValidationError>())
+        .map(|e| e.field.clone());
+    let py_err = QueryValidationError::new_err(format!("{:?}", err));
+
+    Python::with_gil(|py| {
+        let value = py_err.value(py);
+        value.setattr("kind", "validation").ok();
+        value.setattr("field", field).ok();
+    });
+
+    py_err
+}
+
+/// Class of an error returned while talking to the source hypersync instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The request timed out
+    Timeout,
+    /// Couldn't connect to the server or the connection was dropped
+    Connection,
+    /// The server responded with 429 Too Many Requests
+    RateLimit,
+    /// The server responded with 401 or 403
+    Auth,
+    /// The server responded with a 5xx status code
+    Server,
+    /// The server responded with any other non success status code
+    Http,
+    /// The response couldn't be decoded
+    Decode,
+    /// Anything else
+    Other,
+}
+
+impl ErrorKind {
+    /// Classifies an error by walking its chain of causes.
+    pub fn of(err: &anyhow::Error) -> Self {
+        if let Some(status) = status_code(err) {
+            return Self::from_status(status);
+        }
+
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                if e.is_timeout() {
+                    return Self::Timeout;
+                }
+                if e.is_connect() || e.is_request() || e.is_body() {
+                    return Self::Connection;
+                }
+                if e.is_decode() {
+                    return Self::Decode;
+                }
+            }
+        }
+
+        let is_decode = err.chain().any(|cause| {
+            let msg = cause.to_string();
+            msg.starts_with("parse query response") || msg.starts_with("read response body json")
+        });
+        if is_decode {
+            return Self::Decode;
+        }
+
+        Self::Other
+    }
+
+    fn from_status(status: u16) -> Self {
+        match status {
+            429 => Self::RateLimit,
+            401 | 403 => Self::Auth,
+            500..=599 => Self::Server,
+            _ => Self::Http,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::Connection => "connection",
+            Self::RateLimit => "rate_limit",
+            Self::Auth => "auth",
+            Self::Server => "server",
+            Self::Http => "http",
+            Self::Decode => "decode",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "timeout" => Ok(Self::Timeout),
+            "connection" => Ok(Self::Connection),
+            "rate_limit" => Ok(Self::RateLimit),
+            "auth" => Ok(Self::Auth),
+            "server" => Ok(Self::Server),
+            "http" => Ok(Self::Http),
+            "decode" => Ok(Self::Decode),
+            "other" => Ok(Self::Other),
+            _ => Err(anyhow!("unknown error class: {}", name)),
+        }
+    }
+}
+
+/// Http status code of a non success response.
+///
+/// hyperfuel_client reports these as "http response status code {status}, ...".
+pub fn status_code(err: &anyhow::Error) -> Option<u16> {
+    for cause in err.chain() {
+        if let Some(status) = cause
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+        {
+            return Some(status.as_u16());
+        }
+
+        let msg = cause.to_string();
+        if let Some(rest) = msg.strip_prefix("http response status code ") {
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if let Ok(status) = digits.parse() {
+                return Some(status);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_code_from_message() {
+        let err = anyhow!("http response status code 429, msg: slow down").context("send request");
+        assert_eq!(status_code(&err), Some(429));
+        assert_eq!(ErrorKind::of(&err), ErrorKind::RateLimit);
+
+        assert_eq!(status_code(&anyhow!("something else")), None);
+    }
+
+    #[test]
+    fn kind_from_status() {
+        assert_eq!(ErrorKind::from_status(401), ErrorKind::Auth);
+        assert_eq!(ErrorKind::from_status(403), ErrorKind::Auth);
+        assert_eq!(ErrorKind::from_status(429), ErrorKind::RateLimit);
+        assert_eq!(ErrorKind::from_status(503), ErrorKind::Server);
+        assert_eq!(ErrorKind::from_status(404), ErrorKind::Http);
+    }
+
+    #[test]
+    fn kind_of_decode_error() {
+        let err = anyhow!("invalid length").context("parse query response");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Decode);
+        assert_eq!(ErrorKind::of(&anyhow!("other")), ErrorKind::Other);
+    }
+
+    #[test]
+    fn kind_names_round_trip() {
+        let kinds = [
+            ErrorKind::Timeout,
+            ErrorKind::Connection,
+            ErrorKind::RateLimit,
+            ErrorKind::Auth,
+            ErrorKind::Server,
+            ErrorKind::Http,
+            ErrorKind::Decode,
+            ErrorKind::Other,
+        ];
+        for kind in kinds {
+            assert_eq!(ErrorKind::parse(kind.as_str()).unwrap(), kind);
+        }
+        assert!(ErrorKind::parse("nope").is_err());
+    }
+}
@@ -0,0 +1,891 @@
+//! Driver of the folder exports, shared by all output formats.
+//!
+//! The driver resumes from the checkpoint in the output folder, fetches the pages of the query
+//! up to its end block and writes them to parts, a set of files with one file per table. A part
+//! is committed to the checkpoint when it is finished, so an interrupted export continues after
+//! the last committed part. The formats only implement [ExportFormat] to write the files.
+
+use std::future::Future;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use arrow2::datatypes::Schema;
+use futures::{stream::BoxStream, StreamExt};
+use hyperfuel_client::ArrowBatch;
+use hyperfuel_net_types::Query;
+use hyperfuel_schema::project_schema;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::column_mapping::{ColumnTypes, Mapping};
+use crate::config::ExportOptions;
+use crate::parallel::{fetch_pages, ParallelOptions};
+use crate::stream::{check_next_block, Page};
+use crate::HyperfuelClient;
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// Tables of an export, every part has a file per table in this order.
+pub const TABLES: [&str; 5] = ["block", "transaction", "receipt", "input", "output"];
+
+/// Folders of the tables in a partitioned output.
+const TABLE_DIRS: [&str; 5] = ["blocks", "transactions", "receipts", "inputs", "outputs"];
+
+/// A page of an export, the arrow or typed response of a query.
+pub trait ExportPage: Page + Sized {
+    /// Number of rows of each table in the page.
+    fn rows(&self) -> TableStats;
+
+    /// Fetches the page that starts at the from_block of the query.
+    fn fetch(
+        client: HyperfuelClient,
+        query: Query,
+    ) -> impl Future<Output = Result<Self>> + Send + 'static;
+}
+
+impl ExportPage for hyperfuel_client::QueryResponse {
+    fn rows(&self) -> TableStats {
+        TableStats::rows_of(self)
+    }
+
+    async fn fetch(client: HyperfuelClient, query: Query) -> Result<Self> {
+        client.fetch_arrow_data(&query).await
+    }
+}
+
+impl ExportPage for hyperfuel_client::QueryResponseTyped {
+    fn rows(&self) -> TableStats {
+        let data = &self.data;
+        TableStats::from_array([
+            data.blocks.len() as u64,
+            data.transactions.len() as u64,
+            data.receipts.len() as u64,
+            data.inputs.len() as u64,
+            data.outputs.len() as u64,
+        ])
+    }
+
+    async fn fetch(client: HyperfuelClient, query: Query) -> Result<Self> {
+        client.fetch_data(&query).await
+    }
+}
+
+/// An output format of the exports, writes the pages to the files of a part.
+pub trait ExportFormat: Sync {
+    type Page: ExportPage;
+    /// Open files of a part
+    type Part: Send;
+
+    /// Extension of the files, e.g. "parquet".
+    fn extension(&self) -> &'static str;
+
+    /// Target types of the mapped columns, the format writes the other columns as they are.
+    fn column_types(&self) -> ColumnTypes {
+        ColumnTypes::new()
+    }
+
+    /// Whether amounts are rendered as hex, only applies to text formats.
+    fn hex_numbers(&self) -> bool {
+        false
+    }
+
+    /// Creates the files of a part, `files` has a path per table in the order of [TABLES].
+    fn create(&self, files: Vec<PathBuf>) -> impl Future<Output = Result<Self::Part>> + Send;
+
+    fn write(
+        &self,
+        part: &mut Self::Part,
+        page: Self::Page,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Finishes the files of the part, they are complete once this returns.
+    fn finish(&self, part: Self::Part) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Runs an export of the query into the folder at `path`.
+///
+/// The query runs to its (exclusive) to_block, or up to and including the height of the source if
+/// it has none. With `parallel`, the pages are fetched by several workers and written in block order.
+pub async fn run<F: ExportFormat>(
+    client: &HyperfuelClient,
+    query: Query,
+    path: String,
+    format: &F,
+    options: &ExportOptions,
+    parallel: Option<ParallelOptions>,
+    progress: Option<PyObject>,
+) -> Result<ExportSummary> {
+    let start = Instant::now();
+    let mut query = query;
+
+    let path = PathBuf::from(path);
+
+    tokio::fs::create_dir_all(&path)
+        .await
+        .context("create output dir")?;
+
+    let file_format = FileFormat::of(format);
+    let mut checkpoint = match Checkpoint::load(&path).await? {
+        Some(checkpoint) if options.resume => {
+            checkpoint.check(&query, options.partition_blocks, &file_format)?;
+            checkpoint
+        }
+        Some(previous) => {
+            remove_files(&path, &previous.files).await?;
+            previous.discard(&path).await?;
+            Checkpoint::new(&query, options.partition_blocks, file_format)?
+        }
+        None => Checkpoint::new(&query, options.partition_blocks, file_format)?,
+    };
+    let resumed = checkpoint.parts > 0;
+    query.from_block = checkpoint.next_block;
+
+    let to_block = client.fetch_end_block(query.to_block).await?;
+
+    let layout = match options.partition_blocks {
+        Some(blocks) => Layout::Partitioned(blocks),
+        None => Layout::Flat,
+    };
+
+    let mut pages = match parallel {
+        Some(parallel) => Pages::Parallel(fetch_pages(
+            client.clone(),
+            query.clone(),
+            to_block,
+            parallel,
+            options.partition_blocks,
+            F::Page::fetch,
+        )),
+        None => Pages::Sequential,
+    };
+
+    // an export that starts from scratch always creates its files, even if there is no data
+    let mut part = if checkpoint.parts == 0 || query.from_block < to_block {
+        Some(Part::create(&path, layout, &checkpoint, query.from_block, format).await?)
+    } else {
+        None
+    };
+
+    while query.from_block < to_block {
+        // a page never crosses the end of the partition it starts in
+        let partition_end = layout.partition_end(query.from_block);
+
+        let mut current = match part.take() {
+            Some(current) if current.partition_end == partition_end => current,
+            previous => {
+                if let Some(previous) = previous {
+                    previous
+                        .commit(&path, &mut checkpoint, query.from_block, format)
+                        .await?;
+                }
+                Part::create(&path, layout, &checkpoint, query.from_block, format).await?
+            }
+        };
+
+        query.to_block = Some(partition_end.min(to_block));
+        let page = pages.next(client, &query).await?;
+        query.from_block = check_next_block(query.from_block, page.next_block())?;
+
+        current.rows += page.rows();
+        format.write(&mut current.writer, page).await?;
+
+        let mut rows = checkpoint.rows;
+        rows += current.rows;
+        report_progress(&progress, checkpoint.progress(query.from_block, rows))?;
+
+        let blocks_written = query.from_block.saturating_sub(current.from_block);
+        if blocks_written >= options.checkpoint_interval_blocks
+            || current.rows.max() >= options.max_rows_per_file
+        {
+            current
+                .commit(&path, &mut checkpoint, query.from_block, format)
+                .await?;
+        } else {
+            part = Some(current);
+        }
+    }
+
+    if let Some(current) = part.take() {
+        current
+            .commit(&path, &mut checkpoint, query.from_block, format)
+            .await?;
+    }
+
+    Ok(checkpoint.summary(start.elapsed().as_millis() as u64, resumed))
+}
+
+/// Where the pages of an export come from.
+enum Pages<P> {
+    /// A request per page, made when the previous page is written
+    Sequential,
+    /// Pages of chunks that are fetched in parallel, in block order. The chunks end at
+    /// the partition ends so pages never cross them, same as the sequential ones.
+    Parallel(BoxStream<'static, Result<P>>),
+}
+
+impl<P: ExportPage> Pages<P> {
+    /// The next page of the export, starting at the from_block of the query.
+    async fn next(&mut self, client: &HyperfuelClient, query: &Query) -> Result<P> {
+        match self {
+            Self::Sequential => P::fetch(client.clone(), query.clone())
+                .await
+                .context("send query"),
+            Self::Parallel(pages) => pages
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("parallel fetch ended before block {}", query.from_block))?,
+        }
+    }
+}
+
+/// Where the files of the parts of an export are written.
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// A file per table in the output folder, the following parts get a numbered suffix
+    /// ('receipt.parquet', 'receipt.1.parquet', ...).
+    Flat,
+    /// Hive style partitions of the given number of blocks per table
+    /// ('receipts/block_bucket=1000000/part-0.parquet', ...).
+    Partitioned(u64),
+}
+
+impl Layout {
+    /// First block after the partition that contains the given block.
+    fn partition_end(&self, block: u64) -> u64 {
+        match self {
+            Self::Flat => u64::MAX,
+            Self::Partitioned(blocks) => (block / blocks).saturating_add(1).saturating_mul(*blocks),
+        }
+    }
+
+    /// Paths of the files of the next part that starts at the given block, relative to the output folder.
+    fn part_files(
+        &self,
+        checkpoint: &Checkpoint,
+        from_block: u64,
+        extension: &str,
+    ) -> Vec<PathBuf> {
+        match self {
+            Self::Flat => TABLES
+                .iter()
+                .map(|table| {
+                    if checkpoint.parts == 0 {
+                        PathBuf::from(format!("{}.{}", table, extension))
+                    } else {
+                        PathBuf::from(format!("{}.{}.{}", table, checkpoint.parts, extension))
+                    }
+                })
+                .collect(),
+            Self::Partitioned(blocks) => TABLE_DIRS
+                .iter()
+                .map(|dir| {
+                    let bucket = from_block / blocks * blocks;
+                    let dir = Path::new(dir).join(format!("block_bucket={}", bucket));
+                    let part = checkpoint.files_in(&dir);
+                    dir.join(format!("part-{}.{}", part, extension))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Schemas of the tables with the selected fields, after the column mapping is applied.
+pub fn table_schemas(query: &Query, mapping: &Mapping) -> Result<[Schema; 5]> {
+    let fields = &query.field_selection;
+    Ok([
+        project_schema(
+            &mapping.block.map_schema(&hyperfuel_schema::block_header()),
+            &fields.block,
+        )?,
+        project_schema(
+            &mapping
+                .transaction
+                .map_schema(&hyperfuel_schema::transaction()),
+            &fields.transaction,
+        )?,
+        project_schema(
+            &mapping.receipt.map_schema(&hyperfuel_schema::receipt()),
+            &fields.receipt,
+        )?,
+        project_schema(
+            &mapping.input.map_schema(&hyperfuel_schema::input()),
+            &fields.input,
+        )?,
+        project_schema(
+            &mapping.output.map_schema(&hyperfuel_schema::output()),
+            &fields.output,
+        )?,
+    ])
+}
+
+/// Casts the tables of a response with the column mapping, in the order of [TABLES].
+pub fn cast_tables(
+    data: hyperfuel_client::QueryResponseData,
+    mapping: &Mapping,
+) -> Result<[Vec<ArrowBatch>; 5]> {
+    Ok([
+        mapping.block.cast_batches(data.blocks)?,
+        mapping.transaction.cast_batches(data.transactions)?,
+        mapping.receipt.cast_batches(data.receipts)?,
+        mapping.input.cast_batches(data.inputs)?,
+        mapping.output.cast_batches(data.outputs)?,
+    ])
+}
+
+/// Removes the files written by a previous export.
+async fn remove_files(path: &Path, files: &[String]) -> Result<()> {
+    for file in files {
+        match tokio::fs::remove_file(path.join(file)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(anyhow::Error::new(e).context("remove previous output"));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// The files of a part of the export that is being written.
+struct Part<W> {
+    from_block: u64,
+    /// End of the pages that are written to this part
+    partition_end: u64,
+    /// Paths of the files relative to the output folder, one per table
+    files: Vec<PathBuf>,
+    writer: W,
+    rows: TableStats,
+}
+
+impl<W> Part<W> {
+    async fn create<F: ExportFormat<Part = W>>(
+        path: &Path,
+        layout: Layout,
+        checkpoint: &Checkpoint,
+        from_block: u64,
+        format: &F,
+    ) -> Result<Self> {
+        let files = layout.part_files(checkpoint, from_block, format.extension());
+
+        let mut paths = Vec::with_capacity(files.len());
+        for file in files.iter() {
+            let file = path.join(file);
+            if let Some(dir) = file.parent() {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .context("create partition dir")?;
+            }
+            paths.push(file);
+        }
+
+        Ok(Self {
+            from_block,
+            partition_end: layout.partition_end(from_block),
+            files,
+            writer: format.create(paths).await?,
+            rows: TableStats::default(),
+        })
+    }
+
+    /// Finishes the files and records them in the checkpoint.
+    async fn commit<F: ExportFormat<Part = W>>(
+        self,
+        path: &Path,
+        checkpoint: &mut Checkpoint,
+        next_block: u64,
+        format: &F,
+    ) -> Result<()> {
+        format.finish(self.writer).await?;
+
+        let mut bytes = [0; 5];
+        for ((table, file), size) in TABLES.iter().zip(self.files).zip(bytes.iter_mut()) {
+            *size = tokio::fs::metadata(path.join(&file))
+                .await
+                .with_context(|| format!("get size of {} file", table))?
+                .len();
+            checkpoint.files.push(file.to_string_lossy().into_owned());
+        }
+
+        checkpoint.next_block = next_block;
+        checkpoint.parts += 1;
+        checkpoint.rows += self.rows;
+        checkpoint.bytes += TableStats::from_array(bytes);
+
+        checkpoint.store(path).await
+    }
+}
+
+/// A count per table, used for the rows and bytes written by an export.
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStats {
+    pub block: u64,
+    pub transaction: u64,
+    pub receipt: u64,
+    pub input: u64,
+    pub output: u64,
+}
+
+#[pymethods]
+impl TableStats {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+impl TableStats {
+    /// Counts in the order block, transaction, receipt, input, output.
+    pub fn from_array(counts: [u64; 5]) -> Self {
+        let [block, transaction, receipt, input, output] = counts;
+        Self {
+            block,
+            transaction,
+            receipt,
+            input,
+            output,
+        }
+    }
+
+    /// Largest count of any table.
+    pub fn max(&self) -> u64 {
+        [
+            self.block,
+            self.transaction,
+            self.receipt,
+            self.input,
+            self.output,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+    }
+
+    /// Number of rows of each table in an arrow response.
+    pub fn rows_of(res: &hyperfuel_client::QueryResponse) -> Self {
+        let rows = |batches: &[hyperfuel_client::ArrowBatch]| {
+            batches.iter().map(|b| b.chunk.len() as u64).sum::<u64>()
+        };
+        Self::from_array([
+            rows(&res.data.blocks),
+            rows(&res.data.transactions),
+            rows(&res.data.receipts),
+            rows(&res.data.inputs),
+            rows(&res.data.outputs),
+        ])
+    }
+}
+
+impl AddAssign for TableStats {
+    fn add_assign(&mut self, other: Self) {
+        self.block += other.block;
+        self.transaction += other.transaction;
+        self.receipt += other.receipt;
+        self.input += other.input;
+        self.output += other.output;
+    }
+}
+
+/// Progress of an export, passed to the progress callback after every page.
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Clone)]
+pub struct ExportProgress {
+    /// Block the export continues from
+    pub next_block: u64,
+    /// Number of blocks processed so far, including the ones of previous runs that were resumed
+    pub blocks_processed: u64,
+    /// Rows written per table so far
+    pub rows: TableStats,
+}
+
+#[pymethods]
+impl ExportProgress {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+/// Summary of a finished export.
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Clone)]
+pub struct ExportSummary {
+    /// Block the export stopped at, a rerun with the same query continues from here
+    pub next_block: u64,
+    /// Number of blocks processed, including the ones of previous runs that were resumed
+    pub blocks_processed: u64,
+    /// Rows written per table
+    pub rows: TableStats,
+    /// Size of the written files per table in bytes
+    pub bytes: TableStats,
+    /// Time this run took in milliseconds
+    pub elapsed_millis: u64,
+    /// Whether the export continued from the checkpoint of a previous run
+    pub resumed: bool,
+}
+
+#[pymethods]
+impl ExportSummary {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+/// State of an export that is committed to the output folder, a rerun of the same query
+/// continues from it instead of starting over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The query of the export as it was started, without its to_block so a later run
+    /// can append the following blocks
+    query: serde_json::Value,
+    /// Number of blocks per partition if the output is partitioned
+    pub partition_blocks: Option<u64>,
+    /// Format of the files
+    #[serde(flatten)]
+    pub format: FileFormat,
+    /// First block of the export
+    pub from_block: u64,
+    /// All blocks before this one are written to committed parts
+    pub next_block: u64,
+    /// Number of committed parts
+    pub parts: u64,
+    /// Paths of the committed files, relative to the output folder
+    pub files: Vec<String>,
+    /// Rows in the committed parts
+    pub rows: TableStats,
+    /// Size of the committed parts in bytes
+    pub bytes: TableStats,
+}
+
+impl Checkpoint {
+    pub fn new(query: &Query, partition_blocks: Option<u64>, format: FileFormat) -> Result<Self> {
+        Ok(Self {
+            query: query_key(query)?,
+            partition_blocks,
+            format,
+            from_block: query.from_block,
+            next_block: query.from_block,
+            parts: 0,
+            files: Vec::new(),
+            rows: TableStats::default(),
+            bytes: TableStats::default(),
+        })
+    }
+
+    fn path(dir: &Path) -> PathBuf {
+        dir.join(CHECKPOINT_FILE)
+    }
+
+    /// Reads the checkpoint of a previous run from the output folder, if there is one.
+    pub async fn load(dir: &Path) -> Result<Option<Self>> {
+        let data = match tokio::fs::read(Self::path(dir)).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(anyhow::Error::new(e).context("read checkpoint")),
+        };
+
+        serde_json::from_slice(&data)
+            .context("parse checkpoint")
+            .map(Some)
+    }
+
+    /// Checks that the checkpoint was written by an export of the same query with the same
+    /// partitioning and file format, only the to_block of the query can differ.
+    pub fn check(
+        &self,
+        query: &Query,
+        partition_blocks: Option<u64>,
+        format: &FileFormat,
+    ) -> Result<()> {
+        if self.query != query_key(query)? {
+            return Err(anyhow!(
+                "the output folder contains a checkpoint of a different query, \
+                use another folder or disable resume to start over"
+            ));
+        }
+
+        if self.partition_blocks != partition_blocks {
+            return Err(anyhow!(
+                "the output folder was written with partition_blocks {:?}, \
+                use the same partitioning or disable resume to start over",
+                self.partition_blocks
+            ));
+        }
+
+        if self.format.extension != format.extension {
+            return Err(anyhow!(
+                "the output folder contains {} files, \
+                use another folder or disable resume to start over",
+                self.format.extension
+            ));
+        }
+
+        // parts with other column types couldn't be read as a single dataset
+        if self.format.column_mapping != format.column_mapping {
+            return Err(anyhow!(
+                "the output folder was written with column_mapping {:?}, \
+                use the same column mapping or disable resume to start over",
+                self.format.column_mapping
+            ));
+        }
+
+        if self.format.hex_numbers != format.hex_numbers {
+            return Err(anyhow!(
+                "the output folder was written with hex_numbers {}, \
+                use the same setting or disable resume to start over",
+                self.format.hex_numbers
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Number of committed files in a folder of the output.
+    pub fn files_in(&self, dir: &Path) -> u64 {
+        self.files
+            .iter()
+            .filter(|file| Path::new(file).parent() == Some(dir))
+            .count() as u64
+    }
+
+    /// Commits the checkpoint, it is written to a temporary file first so a crash
+    /// never leaves a partially written checkpoint behind.
+    pub async fn store(&self, dir: &Path) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self).context("serialize checkpoint")?;
+
+        let tmp = dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        tokio::fs::write(&tmp, data)
+            .await
+            .context("write checkpoint")?;
+        tokio::fs::rename(&tmp, Self::path(dir))
+            .await
+            .context("commit checkpoint")
+    }
+
+    /// Removes the checkpoint from the output folder, after the parts it refers to were removed.
+    pub async fn discard(self, dir: &Path) -> Result<()> {
+        tokio::fs::remove_file(Self::path(dir))
+            .await
+            .context("remove checkpoint")
+    }
+
+    pub fn progress(&self, next_block: u64, rows: TableStats) -> ExportProgress {
+        ExportProgress {
+            next_block,
+            blocks_processed: next_block.saturating_sub(self.from_block),
+            rows,
+        }
+    }
+
+    pub fn summary(&self, elapsed_millis: u64, resumed: bool) -> ExportSummary {
+        ExportSummary {
+            next_block: self.next_block,
+            blocks_processed: self.next_block.saturating_sub(self.from_block),
+            rows: self.rows,
+            bytes: self.bytes,
+            elapsed_millis,
+            resumed,
+        }
+    }
+}
+
+/// Settings that shape the files of an export, all parts of an export are written with the same.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileFormat {
+    /// Extension of the files, checkpoints written before there were other formats are parquet
+    #[serde(default = "parquet_extension")]
+    pub extension: String,
+    /// Target types of the mapped columns per table
+    #[serde(default)]
+    pub column_mapping: ColumnTypes,
+    /// Whether amounts are rendered as hex in text files
+    #[serde(default)]
+    pub hex_numbers: bool,
+}
+
+impl FileFormat {
+    pub fn of<F: ExportFormat>(format: &F) -> Self {
+        Self {
+            extension: format.extension().to_owned(),
+            column_mapping: format.column_types(),
+            hex_numbers: format.hex_numbers(),
+        }
+    }
+}
+
+fn parquet_extension() -> String {
+    "parquet".to_owned()
+}
+
+/// The query as it is stored in the checkpoint.
+fn query_key(query: &Query) -> Result<serde_json::Value> {
+    let mut query = query.clone();
+    query.to_block = None;
+    serde_json::to_value(&query).context("serialize query")
+}
+
+/// Calls the progress callback of an export, an exception raised by the callback aborts the export.
+pub fn report_progress(callback: &Option<PyObject>, progress: ExportProgress) -> Result<()> {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return Ok(()),
+    };
+
+    Python::with_gil(|py| {
+        callback
+            .call1(py, (progress,))
+            .map(|_| ())
+            .map_err(|e| anyhow!("progress callback failed: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(extension: &str) -> FileFormat {
+        FileFormat {
+            extension: extension.to_owned(),
+            column_mapping: ColumnTypes::new(),
+            hex_numbers: false,
+        }
+    }
+
+    fn query(from_block: u64, to_block: Option<u64>) -> Query {
+        let mut query = Query {
+            from_block,
+            to_block,
+            ..Default::default()
+        };
+        query.field_selection.receipt = ["tx_id".to_owned()].into();
+        query
+    }
+
+    #[test]
+    fn check_same_query() {
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, format("parquet")).unwrap();
+
+        checkpoint
+            .check(&query(10, Some(20)), None, &format("parquet"))
+            .unwrap();
+    }
+
+    #[test]
+    fn check_other_to_block() {
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, format("csv")).unwrap();
+
+        checkpoint
+            .check(&query(10, Some(30)), None, &format("csv"))
+            .unwrap();
+        checkpoint
+            .check(&query(10, None), None, &format("csv"))
+            .unwrap();
+    }
+
+    #[test]
+    fn check_other_query() {
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, format("parquet")).unwrap();
+
+        assert!(checkpoint
+            .check(&query(11, Some(20)), None, &format("parquet"))
+            .is_err());
+
+        let mut other = query(10, Some(20));
+        other.field_selection.receipt.insert("data".to_owned());
+        assert!(checkpoint.check(&other, None, &format("parquet")).is_err());
+    }
+
+    #[test]
+    fn check_other_partitioning() {
+        let checkpoint =
+            Checkpoint::new(&query(10, Some(20)), Some(100), format("parquet")).unwrap();
+
+        checkpoint
+            .check(&query(10, Some(20)), Some(100), &format("parquet"))
+            .unwrap();
+        assert!(checkpoint
+            .check(&query(10, Some(20)), Some(1000), &format("parquet"))
+            .is_err());
+        assert!(checkpoint
+            .check(&query(10, Some(20)), None, &format("parquet"))
+            .is_err());
+    }
+
+    #[test]
+    fn check_other_format() {
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, format("arrow")).unwrap();
+
+        assert!(checkpoint
+            .check(&query(10, Some(20)), None, &format("arrows"))
+            .is_err());
+    }
+
+    #[test]
+    fn check_other_column_mapping() {
+        let mut mapped = format("parquet");
+        mapped.column_mapping.insert(
+            "block".to_owned(),
+            [("height".to_owned(), "Int64".to_owned())].into(),
+        );
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, mapped.clone()).unwrap();
+
+        checkpoint
+            .check(&query(10, Some(20)), None, &mapped)
+            .unwrap();
+        assert!(checkpoint
+            .check(&query(10, Some(20)), None, &format("parquet"))
+            .is_err());
+
+        let mut other = mapped.clone();
+        other
+            .column_mapping
+            .get_mut("block")
+            .unwrap()
+            .insert("height".to_owned(), "UInt32".to_owned());
+        assert!(checkpoint
+            .check(&query(10, Some(20)), None, &other)
+            .is_err());
+    }
+
+    #[test]
+    fn check_other_hex_numbers() {
+        let mut hex = format("csv");
+        hex.hex_numbers = true;
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, hex.clone()).unwrap();
+
+        checkpoint.check(&query(10, Some(20)), None, &hex).unwrap();
+        assert!(checkpoint
+            .check(&query(10, Some(20)), None, &format("csv"))
+            .is_err());
+    }
+
+    #[test]
+    fn checkpoint_without_extension_is_parquet() {
+        let checkpoint = Checkpoint::new(&query(10, Some(20)), None, format("parquet")).unwrap();
+        let mut stored = serde_json::to_value(&checkpoint).unwrap();
+        let stored_fields = stored.as_object_mut().unwrap();
+        stored_fields.remove("extension");
+        stored_fields.remove("column_mapping");
+        stored_fields.remove("hex_numbers");
+
+        let loaded: Checkpoint = serde_json::from_value(stored).unwrap();
+        loaded
+            .check(&query(10, Some(20)), None, &format("parquet"))
+            .unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use arrow2::{
+    datatypes::Schema,
+    io::ipc::write::{file_async::FileSink, stream_async::StreamSink, StreamWriter, WriteOptions},
+};
+use futures::SinkExt;
+use hyperfuel_client::ArrowBatch;
+use hyperfuel_net_types::Query;
+use pyo3::PyObject;
+use tokio::fs::File;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::column_mapping::{ColumnTypes, Mapping};
+use crate::config::{IpcFormat, IpcOptions};
+use crate::export::{self, cast_tables, table_schemas, ExportFormat, ExportSummary, TABLES};
+use crate::HyperfuelClient;
+
+pub async fn create_ipc_folder(
+    client: &HyperfuelClient,
+    query: Query,
+    path: String,
+    mapping: &Mapping,
+    options: &IpcOptions,
+    progress: Option<PyObject>,
+) -> Result<ExportSummary> {
+    let ipc = Ipc {
+        schemas: table_schemas(&query, mapping)?,
+        mapping,
+        format: options.format,
+    };
+
+    export::run(client, query, path, &ipc, &options.export, None, progress).await
+}
+
+/// Writes the tables of an export to arrow IPC files or streams.
+struct Ipc<'a> {
+    /// Schemas of the tables after the column mapping
+    schemas: [Schema; 5],
+    mapping: &'a Mapping,
+    format: IpcFormat,
+}
+
+impl ExportFormat for Ipc<'_> {
+    type Page = hyperfuel_client::QueryResponse;
+    type Part = Vec<TableFile>;
+
+    fn extension(&self) -> &'static str {
+        match self.format {
+            IpcFormat::File => "arrow",
+            IpcFormat::Stream => "arrows",
+        }
+    }
+
+    fn column_types(&self) -> ColumnTypes {
+        self.mapping.column_types()
+    }
+
+    async fn create(&self, files: Vec<PathBuf>) -> Result<Self::Part> {
+        let mut part = Vec::with_capacity(TABLES.len());
+        for ((table, schema), path) in TABLES.into_iter().zip(self.schemas.iter()).zip(files) {
+            part.push(
+                TableFile::create(path, table, schema.clone(), self.format)
+                    .await
+                    .with_context(|| format!("create {} output file", table))?,
+            );
+        }
+
+        Ok(part)
+    }
+
+    async fn write(&self, part: &mut Self::Part, page: Self::Page) -> Result<()> {
+        for (file, batches) in part.iter_mut().zip(cast_tables(page.data, self.mapping)?) {
+            for batch in batches {
+                file.sink
+                    .send(batch)
+                    .await
+                    .with_context(|| format!("write {} chunk to arrow file", file.table))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&self, part: Self::Part) -> Result<()> {
+        for file in part {
+            file.finish().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes the tables of a response as Arrow IPC streams, in the order block, transaction,
+/// receipt, input, output.
+pub fn encode_ipc_streams(
+    res: hyperfuel_client::QueryResponse,
+    query: &Query,
+    mapping: &Mapping,
+) -> Result<[Vec<u8>; 5]> {
+    let schemas = table_schemas(query, mapping)?;
+    let tables = cast_tables(res.data, mapping)?;
+
+    let mut streams: [Vec<u8>; 5] = Default::default();
+    for (((table, schema), batches), out) in TABLES
+        .into_iter()
+        .zip(schemas)
+        .zip(tables)
+        .zip(streams.iter_mut())
+    {
+        let mut writer = StreamWriter::new(Vec::new(), WriteOptions { compression: None });
+        writer
+            .start(&schema, None)
+            .with_context(|| format!("start {} ipc stream", table))?;
+        for batch in batches {
+            writer
+                .write(&batch.chunk, None)
+                .with_context(|| format!("write {} chunk to ipc stream", table))?;
+        }
+        writer
+            .finish()
+            .with_context(|| format!("finish {} ipc stream", table))?;
+
+        *out = writer.into_inner();
+    }
+
+    Ok(streams)
+}
+
+/// The output file of a table.
+struct TableFile {
+    table: &'static str,
+    sink: IpcSink,
+}
+
+impl TableFile {
+    async fn create(
+        path: PathBuf,
+        table: &'static str,
+        schema: Schema,
+        format: IpcFormat,
+    ) -> Result<Self> {
+        let file = File::create(&path)
+            .await
+            .context("create arrow file")?
+            .compat_write();
+        let options = WriteOptions { compression: None };
+
+        let sink = match format {
+            IpcFormat::File => IpcSink::File(FileSink::new(file, schema, None, options)),
+            IpcFormat::Stream => IpcSink::Stream(StreamSink::new(file, &schema, None, options)),
+        };
+
+        Ok(Self { table, sink })
+    }
+
+    async fn finish(self) -> Result<()> {
+        self.sink
+            .close()
+            .await
+            .with_context(|| format!("finish writing {} arrow file", self.table))
+    }
+}
+
+/// Writer of an arrow IPC file or stream.
+enum IpcSink {
+    File(FileSink<'static, Compat<File>>),
+    Stream(StreamSink<'static, Compat<File>>),
+}
+
+impl IpcSink {
+    async fn send(&mut self, batch: ArrowBatch) -> arrow2::error::Result<()> {
+        match self {
+            Self::File(sink) => sink.send(batch.chunk.into()).await,
+            Self::Stream(sink) => sink.send(batch.chunk.into()).await,
+        }
+    }
+
+    async fn close(self) -> arrow2::error::Result<()> {
+        match self {
+            Self::File(mut sink) => sink.close().await,
+            Self::Stream(mut sink) => sink.close().await,
+        }
+    }
+}
@@ -1,43 +1,455 @@
+use abi::AbiRegistry;
 use anyhow::{Context, Result};
 use arrow2::datatypes::Field;
 use arrow2::ffi;
-use arrow2::{array::StructArray, datatypes::DataType};
+use arrow2::{
+    array::{Array, StructArray},
+    datatypes::DataType,
+};
+use cache::QueryCache;
 use hyperfuel_client::ArrowBatch;
+use limit::RequestLimiter;
+use metrics::{ClientStats, Measured, Metrics};
 use pyo3::ffi::Py_uintptr_t;
 use pyo3_asyncio::tokio::future_into_py;
 use response::{LogResponse, QueryResponseArrow, QueryResponseArrowData, QueryResponseTyped};
+use retry::RetryPolicy;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Instant;
+use stream::{ArrowStream, LogStream, QueryResponseStream};
 
+mod abi;
+mod blocking;
+mod cache;
+mod column_mapping;
 mod config;
+mod error;
+mod export;
+mod ipc_out;
+mod limit;
+mod logging;
+mod metrics;
+mod parallel;
+mod parquet_out;
 mod query;
+mod query_builder;
 mod response;
+mod retry;
+mod stream;
+mod text_out;
 mod types;
 
+use error::{decode_err, py_err, validation_err};
 use pyo3::{
-    exceptions::{PyIOError, PyValueError},
+    exceptions::PyValueError,
     prelude::*,
+    types::{PyBytes, PyCapsule},
 };
+use std::ffi::CString;
 
-pub use config::Config;
+use column_mapping::{ColumnMapping, Mapping};
+pub use config::{Config, IpcConfig, ParquetConfig, StreamConfig, TextConfig};
+use export::ExportSummary;
+use parallel::ParallelOptions;
+use text_out::TextFormat;
 
 #[pymodule]
-fn hyperfuel(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<HyperfuelClient>()
+fn hyperfuel(py: Python, m: &PyModule) -> PyResult<()> {
+    logging::init();
+
+    m.add_class::<HyperfuelClient>()?;
+    m.add_class::<blocking::HyperfuelClientSync>()?;
+    m.add_class::<query_builder::QueryBuilder>()?;
+    m.add_class::<types::ReceiptType>()?;
+    m.add_class::<types::InputType>()?;
+    m.add_class::<types::OutputType>()?;
+    m.add_class::<types::TransactionType>()?;
+    m.add_class::<types::TransactionStatus>()?;
+    m.add_function(wrap_pyfunction!(query_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(query_from_text, m)?)?;
+    error::add_exceptions(py, m)
+}
+
+/// Serializes a query in the JSON format of hyperfuel_net_types::Query.
+#[pyfunction]
+#[pyo3(signature = (query, pretty = false))]
+fn query_to_json(query: query::Query, pretty: bool) -> PyResult<String> {
+    query.to_json(pretty).map_err(validation_err)
+}
+
+/// Reads a query from text, format is one of "json", "yaml" or "toml".
+#[pyfunction]
+fn query_from_text(text: &str, format: &str) -> PyResult<query::Query> {
+    let format = query::QueryFormat::from_name(format).map_err(|e| validation_err(e.into()))?;
+    query::Query::from_text(text, format).map_err(validation_err)
 }
 #[pyclass]
+#[derive(Clone)]
 pub struct HyperfuelClient {
     inner: Arc<hyperfuel_client::Client>,
+    retry: Arc<RetryPolicy>,
+    limiter: Arc<RequestLimiter>,
+    metrics: Arc<Metrics>,
+    abis: Arc<AbiRegistry>,
+    cache: Option<Arc<QueryCache>>,
+    native_types: bool,
 }
 
 impl HyperfuelClient {
     fn new_impl(config: Config) -> Result<HyperfuelClient> {
-        env_logger::try_init().ok();
-
+        let retry = RetryPolicy::from_config(&config).context("parse retry config")?;
+        let limiter = RequestLimiter::from_config(&config).context("parse request limits")?;
+        let abis = AbiRegistry::from_config(&config).context("parse abis")?;
+        let cache = QueryCache::from_config(&config).context("open cache")?;
+        let native_types = config.native_types.unwrap_or_default();
         let config = config.try_convert().context("parse config")?;
 
         Ok(HyperfuelClient {
             inner: Arc::new(hyperfuel_client::Client::new(config).context("create client")?),
+            retry: Arc::new(retry),
+            limiter: Arc::new(limiter),
+            metrics: Arc::new(Metrics::default()),
+            abis: Arc::new(abis),
+            cache: cache.map(Arc::new),
+            native_types,
+        })
+    }
+
+    /// Sends a request to the source, every attempt waits for the request limits of the client
+    /// and failed attempts are retried according to the retry policy.
+    /// The request is recorded in the metrics of the client under the given method.
+    async fn send<T, F, Fut>(&self, method: &'static str, op: F) -> Result<T>
+    where
+        T: Measured,
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let start = Instant::now();
+        let mut attempts = 0u64;
+
+        let fut = self.retry.run(|| {
+            attempts += 1;
+            self.limiter.run(&op)
+        });
+
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("hyperfuel_request", method, retries = tracing::field::Empty);
+        #[cfg(feature = "tracing")]
+        let fut = tracing::Instrument::instrument(fut, span.clone());
+
+        let res = fut.await;
+        let retries = attempts.saturating_sub(1);
+
+        #[cfg(feature = "tracing")]
+        span.record("retries", retries);
+
+        self.metrics.record(method, start.elapsed(), retries, &res);
+
+        res
+    }
+
+    async fn fetch_height(&self) -> Result<u64> {
+        self.send("get_height", || self.inner.get_height()).await
+    }
+
+    /// Exclusive end of the block range of a query with the given to_block, capped at the
+    /// head of the source, see `stream::end_block`.
+    async fn fetch_end_block(&self, to_block: Option<u64>) -> Result<u64> {
+        let height = self.fetch_height().await.context("get height of source")?;
+        Ok(stream::end_block(to_block, height))
+    }
+
+    async fn fetch_data(
+        &self,
+        query: &hyperfuel_net_types::Query,
+    ) -> Result<hyperfuel_client::QueryResponseTyped> {
+        self.send("get_data", || self.inner.get_data(query)).await
+    }
+
+    async fn fetch_selected_data(
+        &self,
+        query: &hyperfuel_net_types::Query,
+    ) -> Result<hyperfuel_client::QueryResponseTyped> {
+        self.send("get_selected_data", || self.inner.get_selected_data(query))
+            .await
+    }
+
+    async fn fetch_arrow_data(
+        &self,
+        query: &hyperfuel_net_types::Query,
+    ) -> Result<hyperfuel_client::QueryResponse> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => {
+                return self
+                    .send("get_arrow_data", || self.inner.get_arrow_data(query))
+                    .await
+            }
+        };
+
+        // a broken cache entry is refetched and replaced instead of failing the request
+        match cache.get(query).await {
+            Ok(Some(res)) => return Ok(res),
+            Ok(None) => (),
+            Err(e) => log::warn!("failed to read cached response: {:?}", e),
+        }
+
+        let res = self
+            .send("get_arrow_data", || self.inner.get_arrow_data(query))
+            .await?;
+
+        if let Err(e) = cache.put(query, &res).await {
+            log::warn!("failed to cache response: {:?}", e);
+        }
+
+        Ok(res)
+    }
+
+    async fn fetch_preset_logs(
+        &self,
+        emitting_contracts: &[[u8; 32]],
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<hyperfuel_client::LogResponse> {
+        self.send("preset_query_get_logs", || {
+            self.inner
+                .preset_query_get_logs(emitting_contracts.to_vec(), from_block, to_block)
         })
+        .await
+    }
+
+    async fn get_height_impl(self) -> PyResult<u64> {
+        self.fetch_height().await.map_err(py_err)
+    }
+
+    async fn create_parquet_folder_impl(
+        self,
+        query: query::Query,
+        path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+    ) -> PyResult<ExportSummary> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+        let options = config
+            .unwrap_or_default()
+            .try_convert()
+            .map_err(|e| validation_err(e.into()))?;
+
+        parquet_out::create_parquet_folder(&self, query, path, &mapping, &options, None, progress)
+            .await
+            .map_err(py_err)
+    }
+
+    async fn create_text_folder_impl(
+        self,
+        query: query::Query,
+        path: String,
+        format: TextFormat,
+        config: Option<TextConfig>,
+        progress: Option<PyObject>,
+    ) -> PyResult<ExportSummary> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let options = config
+            .unwrap_or_default()
+            .try_convert()
+            .map_err(|e| validation_err(e.into()))?;
+
+        text_out::create_text_folder(&self, query, path, format, &options, progress)
+            .await
+            .map_err(py_err)
+    }
+
+    async fn create_arrow_folder_impl(
+        self,
+        query: query::Query,
+        path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<IpcConfig>,
+        progress: Option<PyObject>,
+    ) -> PyResult<ExportSummary> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+        let options = config
+            .unwrap_or_default()
+            .try_convert()
+            .map_err(|e| validation_err(e.into()))?;
+
+        ipc_out::create_ipc_folder(&self, query, path, &mapping, &options, progress)
+            .await
+            .map_err(py_err)
+    }
+
+    async fn get_arrow_ipc_impl(
+        self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<QueryResponseArrow> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+
+        let res = self.fetch_arrow_data(&query).await.map_err(py_err)?;
+        let archive_height = res.archive_height;
+        let next_block = res.next_block;
+        let total_execution_time = res.total_execution_time;
+
+        let streams = run_blocking(move || ipc_out::encode_ipc_streams(res, &query, &mapping))
+            .await?
+            .map_err(py_err)?;
+
+        Ok(Python::with_gil(|py| {
+            let [blocks, transactions, receipts, inputs, outputs] =
+                streams.map(|stream| PyBytes::new(py, &stream).into_py(py));
+
+            QueryResponseArrow {
+                archive_height,
+                next_block,
+                total_execution_time,
+                data: QueryResponseArrowData {
+                    blocks,
+                    transactions,
+                    receipts,
+                    inputs,
+                    outputs,
+                },
+            }
+        }))
+    }
+
+    async fn get_data_impl(self, query: query::Query) -> PyResult<QueryResponseTyped> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let res = self.fetch_data(&query).await.map_err(py_err)?;
+
+        let native_types = self.native_types;
+        let mut res = run_blocking(move || QueryResponseTyped::new(res, native_types)).await?;
+        self.abis.decode_calls(&mut res.data.receipts);
+
+        Ok(res)
+    }
+
+    async fn get_selected_data_impl(self, query: query::Query) -> PyResult<QueryResponseTyped> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let res = self.fetch_selected_data(&query).await.map_err(py_err)?;
+
+        let native_types = self.native_types;
+        let mut res = run_blocking(move || QueryResponseTyped::new(res, native_types)).await?;
+        self.abis.decode_calls(&mut res.data.receipts);
+
+        Ok(res)
+    }
+
+    async fn preset_query_get_logs_impl(
+        self,
+        emitting_contracts: Vec<String>,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> PyResult<LogResponse> {
+        // cut the "0x" off the address
+        let mut emitting_contracts_args = vec![];
+        for (i, contract_address) in emitting_contracts.iter().enumerate() {
+            let address: &str = contract_address
+                .strip_prefix("0x")
+                .unwrap_or(contract_address);
+            let address = hex_str_address_to_byte_array(address).map_err(|e| {
+                validation_err(
+                    query::ValidationError::new(format!("emitting_contracts[{}]", i), e).into(),
+                )
+            })?;
+            emitting_contracts_args.push(address)
+        }
+
+        let res = self
+            .fetch_preset_logs(&emitting_contracts_args, from_block, to_block)
+            .await
+            .map_err(py_err)?;
+
+        let native_types = self.native_types;
+        let mut res = run_blocking(move || LogResponse::new(res, native_types)).await?;
+        self.abis.decode_logs(&mut res.data);
+
+        Ok(res)
+    }
+
+    async fn get_logs_impl(self, query: query::LogQuery) -> PyResult<LogResponse> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let res = self.fetch_selected_data(&query).await.map_err(py_err)?;
+
+        let native_types = self.native_types;
+        let mut res = run_blocking(move || LogResponse::from_receipts(res, native_types)).await?;
+        self.abis.decode_logs(&mut res.data);
+
+        Ok(res)
+    }
+
+    async fn get_arrow_data_impl(
+        self,
+        query: query::Query,
+        format: TableFormat,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<QueryResponseArrow> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = Arc::new(parse_column_mapping(column_mapping)?);
+
+        let res = self.fetch_arrow_data(&query).await.map_err(py_err)?;
+
+        convert_arrow_response(res, format, mapping).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn parallel_collect_impl(
+        self,
+        query: query::Query,
+        num_workers: Option<usize>,
+        chunk_blocks: Option<u64>,
+        column_mapping: Option<ColumnMapping>,
+        path: Option<String>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+        let parallel = ParallelOptions::new(
+            num_workers.unwrap_or(parallel::DEFAULT_NUM_WORKERS),
+            chunk_blocks.unwrap_or(parallel::DEFAULT_CHUNK_BLOCKS),
+        )
+        .map_err(|e| validation_err(e.into()))?;
+
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let res = parallel::collect(&self, query, parallel)
+                    .await
+                    .map_err(py_err)?;
+                let res =
+                    convert_arrow_response(res, TableFormat::Pyarrow, Arc::new(mapping)).await?;
+                return Ok(Python::with_gil(|py| res.into_py(py)));
+            }
+        };
+
+        let options = config
+            .unwrap_or_default()
+            .try_convert()
+            .map_err(|e| validation_err(e.into()))?;
+
+        let summary = parquet_out::create_parquet_folder(
+            &self,
+            query,
+            path,
+            &mapping,
+            &options,
+            Some(parallel),
+            progress,
+        )
+        .await
+        .map_err(py_err)?;
+
+        Ok(Python::with_gil(|py| summary.into_py(py)))
     }
 }
 
@@ -46,37 +458,34 @@ impl HyperfuelClient {
     /// Create a new client with given config
     #[new]
     fn new(config: Config) -> PyResult<HyperfuelClient> {
-        Self::new_impl(config).map_err(|e| PyIOError::new_err(format!("{:?}", e)))
+        Self::new_impl(config).map_err(py_err)
+    }
+
+    /// Get the request metrics of the client and all of its clones.
+    ///
+    /// Counts, errors, retries, arrow data sizes and server execution times are aggregated per request
+    /// method, together with a latency histogram. The arrow data size is an estimate of the decoded
+    /// in-memory size of get_arrow_data responses, not the bytes downloaded, and is zero for the
+    /// typed data and log methods. 'to_prometheus' on the result renders them in the
+    /// Prometheus text format. Responses served from the cache are not counted.
+    pub fn stats(&self) -> ClientStats {
+        self.metrics.snapshot()
     }
 
     /// Get the height of the source hypersync instance
+    /// On an error from the source hypersync instance, the request is retried
+    /// according to the retry settings of the client config.
     pub fn get_height<'py>(&'py self, py: Python<'py>) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
-        future_into_py::<_, u64>(py, async move {
-            let height: u64 = inner
-                .get_height()
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            Ok(height)
-        })
+        future_into_py(py, self.clone().get_height_impl())
     }
 
     /// Get the height of the source hypersync instance
-    /// Internally calls get_height.
-    /// On an error from the source hypersync instance, sleeps for
-    /// 1 second (increasing by 1 each failure up to max of 5 seconds)
-    /// and retries query until success.
+    ///
+    /// Deprecated, emits a DeprecationWarning. Use get_height instead, it retries failed
+    /// requests according to the retry settings of the client config.
     pub fn get_height_with_retry<'py>(&'py self, py: Python<'py>) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
-        future_into_py::<_, u64>(py, async move {
-            let height: u64 = inner
-                .get_height_with_retry()
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            Ok(height)
-        })
+        warn_deprecated(py, "get_height_with_retry", "get_height")?;
+        future_into_py(py, self.clone().get_height_impl())
     }
 
     /// Create a parquet file by executing a query.
@@ -87,26 +496,141 @@ impl HyperfuelClient {
     /// the server.
     ///
     /// Path should point to a folder that will contain the parquet files in the end.
+    /// Columns are cast to the types in 'column_mapping' before they are written.
+    ///
+    /// The 'progress' callback is called with an ExportProgress after every page. Written parts are
+    /// committed to a checkpoint file in the folder, every 'checkpoint_interval_blocks' blocks and at the end,
+    /// so an interrupted export of the same query continues from the last committed block when it is run again.
+    /// With 'partition_blocks' in the config the tables are written as Hive style partitions by block range.
+    /// Returns an ExportSummary with the rows and bytes written per table.
     pub fn create_parquet_folder<'py>(
         &'py self,
         query: query::Query,
         path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(
+            py,
+            self.clone()
+                .create_parquet_folder_impl(query, path, column_mapping, config, progress),
+        )
+    }
+
+    /// Run a query over its whole block range with several workers in parallel.
+    ///
+    /// The range is split into chunks of 'chunk_blocks' blocks (default 100_000) and up to 'num_workers'
+    /// chunks (default 4) are fetched at the same time, each with its own pagination loop.
+    /// The query runs to its (exclusive) to_block, or up to and including the height of the source if it has none.
+    ///
+    /// Without a 'path', the pages are merged in block order into a single query response which contains
+    /// pyarrow data, so the whole range has to fit in memory.
+    /// With a 'path', the pages are written in block order to a parquet folder like in create_parquet_folder,
+    /// which takes the same 'config' and 'progress' arguments, and an ExportSummary is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn parallel_collect<'py>(
+        &'py self,
+        query: query::Query,
+        num_workers: Option<usize>,
+        chunk_blocks: Option<u64>,
+        column_mapping: Option<ColumnMapping>,
+        path: Option<String>,
+        config: Option<ParquetConfig>,
+        progress: Option<PyObject>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(
+            py,
+            self.clone().parallel_collect_impl(
+                query,
+                num_workers,
+                chunk_blocks,
+                column_mapping,
+                path,
+                config,
+                progress,
+            ),
+        )
+    }
+
+    /// Create a folder of Arrow IPC files by executing a query, one file per table.
+    ///
+    /// Pages are streamed to the files as they come from the server and committed to a checkpoint,
+    /// like in create_parquet_folder, so an interrupted export is resumed by running it again.
+    /// The IPC file format (Feather v2) is used by default so the files can be memory mapped by other
+    /// processes, 'format' in the config can select the IPC streaming format instead.
+    /// Columns are cast to the types in 'column_mapping' before they are written.
+    /// Returns an ExportSummary with the rows and bytes written per table.
+    pub fn create_arrow_folder<'py>(
+        &'py self,
+        query: query::Query,
+        path: String,
+        column_mapping: Option<ColumnMapping>,
+        config: Option<IpcConfig>,
+        progress: Option<PyObject>,
         py: Python<'py>,
     ) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
+        future_into_py(
+            py,
+            self.clone()
+                .create_arrow_folder_impl(query, path, column_mapping, config, progress),
+        )
+    }
 
-        future_into_py(py, async move {
-            let query = query
-                .try_convert()
-                .map_err(|_e| PyValueError::new_err("parsing query"))?;
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Returns a query response in which every table is bytes in the Arrow IPC streaming format,
+    /// e.g. to be read with 'pyarrow.ipc.open_stream'.
+    /// Columns are cast to the types in 'column_mapping' before they are encoded.
+    pub fn get_arrow_ipc<'py>(
+        &'py self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(py, self.clone().get_arrow_ipc_impl(query, column_mapping))
+    }
 
-            inner
-                .create_parquet_folder(query, path)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
+    /// Create a folder of CSV files by executing a query, one file per table.
+    ///
+    /// Pages are streamed to the files as they come from the server and committed to a checkpoint,
+    /// like in create_parquet_folder, so an interrupted export is resumed by running it again.
+    /// Columns are the selected fields of the typed data, binary fields are hex encoded and integers
+    /// are written as decimal. With 'hex_numbers' set in the config, amounts are written as hex.
+    /// Returns an ExportSummary with the rows and bytes written per table.
+    pub fn create_csv_folder<'py>(
+        &'py self,
+        query: query::Query,
+        path: String,
+        config: Option<TextConfig>,
+        progress: Option<PyObject>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(
+            py,
+            self.clone()
+                .create_text_folder_impl(query, path, TextFormat::Csv, config, progress),
+        )
+    }
 
-            Ok(())
-        })
+    /// Create a folder of newline delimited JSON files by executing a query, one file per table.
+    ///
+    /// Same as create_csv_folder but every row is written as a JSON object on its own line.
+    pub fn create_ndjson_folder<'py>(
+        &'py self,
+        query: query::Query,
+        path: String,
+        config: Option<TextConfig>,
+        progress: Option<PyObject>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(
+            py,
+            self.clone()
+                .create_text_folder_impl(query, path, TextFormat::Ndjson, config, progress),
+        )
     }
 
     /// Send a query request to the source hypersync instance.
@@ -118,20 +642,7 @@ impl HyperfuelClient {
     /// want plus additional data from the loaded transactions.  This functionality is in case you want to associate
     /// receipts, inputs, or outputs with eachother.
     pub fn get_data<'py>(&'py self, query: query::Query, py: Python<'py>) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
-
-        future_into_py::<_, QueryResponseTyped>(py, async move {
-            let query = query
-                .try_convert()
-                .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-
-            let res = inner
-                .get_data(&query)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            Ok(res.into())
-        })
+        future_into_py(py, self.clone().get_data_impl(query))
     }
 
     /// Send a query request to the source hypersync instance.
@@ -143,20 +654,7 @@ impl HyperfuelClient {
         query: query::Query,
         py: Python<'py>,
     ) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
-
-        future_into_py::<_, QueryResponseTyped>(py, async move {
-            let query = query
-                .try_convert()
-                .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-
-            let res = inner
-                .get_selected_data(&query)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            Ok(res.into())
-        })
+        future_into_py(py, self.clone().get_selected_data_impl(query))
     }
 
     /// Send a query request to the source hypersync instance.
@@ -176,34 +674,31 @@ impl HyperfuelClient {
         to_block: Option<u64>,
         py: Python<'py>,
     ) -> PyResult<&'py PyAny> {
-        let inner = Arc::clone(&self.inner);
-
-        // cut the "0x" off the address
-        let mut emitting_contracts_args = vec![];
-        for contract_address in emitting_contracts {
-            let address: &str = if &contract_address[..2] == "0x" {
-                &contract_address[2..]
-            } else {
-                &contract_address
-            };
-            let address = hex_str_address_to_byte_array(address)
-                .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-            emitting_contracts_args.push(address)
-        }
-
-        future_into_py::<_, LogResponse>(py, async move {
-            let res = inner
-                .preset_query_get_logs(emitting_contracts_args, from_block, to_block)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
+        future_into_py(
+            py,
+            self.clone()
+                .preset_query_get_logs_impl(emitting_contracts, from_block, to_block),
+        )
+    }
 
-            Ok(res.into())
-        })
+    /// Send a log query request to the source hypersync instance.
+    ///
+    /// Returns the Log and/or LogData receipts emitted by the selected contracts, optionally
+    /// filtered by log id (rb). Logs of failed transactions are only returned if 'include_failed' is set.
+    /// The response is paginated like any other query, continue from its next_block if it didn't
+    /// reach the to_block of the query.
+    pub fn get_logs<'py>(
+        &'py self,
+        query: query::LogQuery,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(py, self.clone().get_logs_impl(query))
     }
 
     /// Send a query request to the source hypersync instance.
     ///
     /// Returns a query response which contains pyarrow data.
+    /// Columns are cast to the types in 'column_mapping' before they are handed over to pyarrow.
     ///
     /// NOTE: this query returns loads all transactions that your match your receipt, input, or output selections
     /// and applies the field selection to all these loaded transactions.  So your query will return the data you
@@ -212,127 +707,237 @@ impl HyperfuelClient {
     pub fn get_arrow_data<'py>(
         &'py self,
         query: query::Query,
+        column_mapping: Option<ColumnMapping>,
         py: Python<'py>,
     ) -> PyResult<&'py PyAny> {
-        // initialize an array
-        let inner = Arc::clone(&self.inner);
-
-        future_into_py::<_, QueryResponseArrow>(py, async move {
-            let query = query
-                .try_convert()
-                .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-
-            let res = inner
-                .get_arrow_data(&query)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            let blocks = res.data.blocks;
-            let transactions = res.data.transactions;
-            let receipts = res.data.receipts;
-            let inputs = res.data.inputs;
-            let outputs = res.data.outputs;
-
-            let (blocks, transactions, receipts, inputs, outputs) = Python::with_gil(|py| {
-                let pyarrow = py.import("pyarrow")?;
-                let blocks = convert_batch_to_pyarrow_table(py, pyarrow, blocks)?;
-                let transactions = convert_batch_to_pyarrow_table(py, pyarrow, transactions)?;
-                let receipts = convert_batch_to_pyarrow_table(py, pyarrow, receipts)?;
-                let inputs = convert_batch_to_pyarrow_table(py, pyarrow, inputs)?;
-                let outputs = convert_batch_to_pyarrow_table(py, pyarrow, outputs)?;
-
-                Ok::<(PyObject, PyObject, PyObject, PyObject, PyObject), PyErr>((
-                    blocks,
-                    transactions,
-                    receipts,
-                    inputs,
-                    outputs,
-                ))
-            })?;
-
-            let query_response = compose_pyarrow_response(
-                res.archive_height,
-                res.next_block,
-                res.total_execution_time,
-                blocks,
-                transactions,
-                receipts,
-                inputs,
-                outputs,
-            )
-            .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-
-            Ok(query_response)
-        })
+        future_into_py(
+            py,
+            self.clone()
+                .get_arrow_data_impl(query, TableFormat::Pyarrow, column_mapping),
+        )
     }
 
     /// Send a query request to the source hypersync instance.
-    /// On an error from the source hypersync instance, sleeps for
-    /// 1 second (increasing by 1 each failure up to max of 5 seconds)
-    /// and retries query until success.
     ///
-    /// Returns a query response which contains pyarrow data.
-    ///
-    /// NOTE: this query returns loads all transactions that your match your receipt, input, or output selections
-    /// and applies the field selection to all these loaded transactions.  So your query will return the data you
-    /// want plus additional data from the loaded transactions.  This functionality is in case you want to associate
-    /// receipts, inputs, or outputs with eachother.
-    /// Format can be ArrowIpc.
+    /// Deprecated, emits a DeprecationWarning. Use get_arrow_data instead, it retries failed
+    /// requests according to the retry settings of the client config.
     pub fn get_arrow_data_with_retry<'py>(
         &'py self,
         query: query::Query,
+        column_mapping: Option<ColumnMapping>,
         py: Python<'py>,
     ) -> PyResult<&'py PyAny> {
-        // initialize an array
-        let inner = Arc::clone(&self.inner);
-
-        future_into_py::<_, QueryResponseArrow>(py, async move {
-            let query = query
-                .try_convert()
-                .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
-
-            let res = inner
-                .get_arrow_data_with_retry(&query)
-                .await
-                .map_err(|e| PyIOError::new_err(format!("{:?}", e)))?;
-
-            let blocks = res.data.blocks;
-            let transactions = res.data.transactions;
-            let receipts = res.data.receipts;
-            let inputs = res.data.inputs;
-            let outputs = res.data.outputs;
-
-            let (blocks, transactions, receipts, inputs, outputs) = Python::with_gil(|py| {
-                let pyarrow = py.import("pyarrow")?;
-                let blocks = convert_batch_to_pyarrow_table(py, pyarrow, blocks)?;
-                let transactions = convert_batch_to_pyarrow_table(py, pyarrow, transactions)?;
-                let receipts = convert_batch_to_pyarrow_table(py, pyarrow, receipts)?;
-                let inputs = convert_batch_to_pyarrow_table(py, pyarrow, inputs)?;
-                let outputs = convert_batch_to_pyarrow_table(py, pyarrow, outputs)?;
-
-                Ok::<(PyObject, PyObject, PyObject, PyObject, PyObject), PyErr>((
-                    blocks,
-                    transactions,
-                    receipts,
-                    inputs,
-                    outputs,
-                ))
-            })?;
+        warn_deprecated(py, "get_arrow_data_with_retry", "get_arrow_data")?;
+        future_into_py(
+            py,
+            self.clone()
+                .get_arrow_data_impl(query, TableFormat::Pyarrow, column_mapping),
+        )
+    }
 
-            let query_response = compose_pyarrow_response(
-                res.archive_height,
-                res.next_block,
-                res.total_execution_time,
-                blocks,
-                transactions,
-                receipts,
-                inputs,
-                outputs,
-            )
-            .map_err(|e| PyValueError::new_err(format!("{:?}", e)))?;
+    /// Send a query request to the source hypersync instance.
+    ///
+    /// Same as get_arrow_data but returns polars DataFrames, the arrow data is handed over
+    /// to polars directly without going through pyarrow.
+    pub fn get_polars_data<'py>(
+        &'py self,
+        query: query::Query,
+        column_mapping: Option<ColumnMapping>,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        future_into_py(
+            py,
+            self.clone()
+                .get_arrow_data_impl(query, TableFormat::Polars, column_mapping),
+        )
+    }
 
-            Ok(query_response)
-        })
+    /// Stream the results of a query, page by page.
+    ///
+    /// Splits the block range of the query into batches and runs several of them in parallel,
+    /// the pages are yielded in block order. Returns an async iterator that yields typed pages.
+    /// Fetching is paused when the consumer falls behind and stops when the iterator is closed or dropped.
+    ///
+    /// If no 'to_block' is specified, the stream will run up to and including the current height of the source.
+    pub fn stream(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+    ) -> PyResult<QueryResponseStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            false,
+            |client, query| async move { client.fetch_data(&query).await },
+        );
+
+        Ok(QueryResponseStream::new(
+            pages,
+            Arc::clone(&self.abis),
+            self.native_types,
+        ))
+    }
+
+    /// Stream the results of a query, page by page.
+    ///
+    /// Same as stream but yields pages that contain pyarrow data.
+    pub fn stream_arrow(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            false,
+            |client, query| async move { client.fetch_arrow_data(&query).await },
+        );
+
+        Ok(ArrowStream::new(
+            pages,
+            TableFormat::Pyarrow,
+            Arc::new(mapping),
+        ))
+    }
+
+    /// Stream the results of a query, page by page.
+    ///
+    /// Same as stream but yields pages that contain polars DataFrames.
+    pub fn stream_polars(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            false,
+            |client, query| async move { client.fetch_arrow_data(&query).await },
+        );
+
+        Ok(ArrowStream::new(
+            pages,
+            TableFormat::Polars,
+            Arc::new(mapping),
+        ))
+    }
+
+    /// Stream the results of a log query, page by page.
+    ///
+    /// Same as stream but yields log response pages.
+    pub fn stream_logs(
+        &self,
+        query: query::LogQuery,
+        config: Option<StreamConfig>,
+    ) -> PyResult<LogStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            false,
+            |client, query| async move { client.fetch_selected_data(&query).await },
+        );
+
+        Ok(LogStream::new(
+            pages,
+            Arc::clone(&self.abis),
+            self.native_types,
+        ))
+    }
+
+    /// Stream the results of a query and keep following the head of the chain.
+    ///
+    /// Works like stream but doesn't stop once it catches up to the height of the source.
+    /// It polls the height every 'poll_interval_millis' and yields new pages as blocks land.
+    /// If a 'to_block' is specified, the stream stops once it reaches it, otherwise it runs until
+    /// the iterator is closed or dropped.
+    pub fn tail(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+    ) -> PyResult<QueryResponseStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            true,
+            |client, query| async move { client.fetch_data(&query).await },
+        );
+
+        Ok(QueryResponseStream::new(
+            pages,
+            Arc::clone(&self.abis),
+            self.native_types,
+        ))
+    }
+
+    /// Stream the results of a query and keep following the head of the chain.
+    ///
+    /// Same as tail but yields pages that contain pyarrow data.
+    pub fn tail_arrow(
+        &self,
+        query: query::Query,
+        config: Option<StreamConfig>,
+        column_mapping: Option<ColumnMapping>,
+    ) -> PyResult<ArrowStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+        let mapping = parse_column_mapping(column_mapping)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            true,
+            |client, query| async move { client.fetch_arrow_data(&query).await },
+        );
+
+        Ok(ArrowStream::new(
+            pages,
+            TableFormat::Pyarrow,
+            Arc::new(mapping),
+        ))
+    }
+
+    /// Stream the results of a log query and keep following the head of the chain.
+    ///
+    /// Same as tail but yields log response pages.
+    pub fn tail_logs(
+        &self,
+        query: query::LogQuery,
+        config: Option<StreamConfig>,
+    ) -> PyResult<LogStream> {
+        let query = query.try_convert().map_err(validation_err)?;
+
+        let pages = stream::spawn(
+            self.clone(),
+            query,
+            config.unwrap_or_default(),
+            true,
+            |client, query| async move { client.fetch_selected_data(&query).await },
+        );
+
+        Ok(LogStream::new(
+            pages,
+            Arc::clone(&self.abis),
+            self.native_types,
+        ))
     }
 }
 
@@ -349,6 +954,133 @@ fn hex_str_address_to_byte_array(hex_str: &str) -> Result<[u8; 32], String> {
     }
 }
 
+/// Runs cpu heavy conversion work on the blocking thread pool, without holding the GIL
+/// and without stalling the runtime that drives the requests.
+async fn run_blocking<T, F>(f: F) -> PyResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| py_err(anyhow::Error::new(e).context("run conversion")))
+}
+
+/// Python library the tables of an arrow response are exported to.
+#[derive(Debug, Clone, Copy)]
+pub enum TableFormat {
+    Pyarrow,
+    Polars,
+}
+
+impl TableFormat {
+    fn module(self) -> &'static str {
+        match self {
+            Self::Pyarrow => "pyarrow",
+            Self::Polars => "polars",
+        }
+    }
+}
+
+/// Converts the arrow data of a query response into pyarrow tables or polars DataFrames.
+///
+/// The struct arrays are built on the blocking thread pool, the GIL is only taken
+/// for handing them over to python.
+async fn convert_arrow_response(
+    res: hyperfuel_client::QueryResponse,
+    format: TableFormat,
+    mapping: Arc<Mapping>,
+) -> PyResult<QueryResponseArrow> {
+    run_blocking(move || PreparedArrowResponse::new(res, &mapping))
+        .await?
+        .map_err(py_err)?
+        .into_py_tables(format)
+}
+
+/// Emits a DeprecationWarning for a method that was replaced, pointing at the caller of the
+/// python wrapper.
+fn warn_deprecated(py: Python, method: &str, replacement: &str) -> PyResult<()> {
+    PyErr::warn(
+        py,
+        py.get_type::<pyo3::exceptions::PyDeprecationWarning>(),
+        &format!(
+            "{} is deprecated, use {} instead, it retries failed requests according to the client config",
+            method, replacement
+        ),
+        2,
+    )
+}
+
+/// Parses the column mapping of a request, no mapping leaves the data as it is.
+fn parse_column_mapping(column_mapping: Option<ColumnMapping>) -> PyResult<Mapping> {
+    column_mapping
+        .unwrap_or_default()
+        .try_convert()
+        .map_err(validation_err)
+}
+
+/// Arrow data of a query response, converted to struct arrays that are ready to be exported to pyarrow.
+struct PreparedArrowResponse {
+    archive_height: Option<u64>,
+    next_block: u64,
+    total_execution_time: u64,
+    blocks: Option<PreparedTable>,
+    transactions: Option<PreparedTable>,
+    receipts: Option<PreparedTable>,
+    inputs: Option<PreparedTable>,
+    outputs: Option<PreparedTable>,
+}
+
+impl PreparedArrowResponse {
+    /// Casts the mapped columns and builds the struct arrays of all tables, doesn't need the GIL.
+    fn new(res: hyperfuel_client::QueryResponse, mapping: &Mapping) -> Result<Self> {
+        let data = res.data;
+        Ok(Self {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            blocks: PreparedTable::new(mapping.block.cast_batches(data.blocks)?),
+            transactions: PreparedTable::new(mapping.transaction.cast_batches(data.transactions)?),
+            receipts: PreparedTable::new(mapping.receipt.cast_batches(data.receipts)?),
+            inputs: PreparedTable::new(mapping.input.cast_batches(data.inputs)?),
+            outputs: PreparedTable::new(mapping.output.cast_batches(data.outputs)?),
+        })
+    }
+
+    /// Hands the tables over to python, this is the only step that holds the GIL.
+    fn into_py_tables(self, format: TableFormat) -> PyResult<QueryResponseArrow> {
+        let (blocks, transactions, receipts, inputs, outputs) = Python::with_gil(|py| {
+            let module = py.import(format.module())?;
+            let blocks = PreparedTable::to_py(self.blocks, py, module, format)?;
+            let transactions = PreparedTable::to_py(self.transactions, py, module, format)?;
+            let receipts = PreparedTable::to_py(self.receipts, py, module, format)?;
+            let inputs = PreparedTable::to_py(self.inputs, py, module, format)?;
+            let outputs = PreparedTable::to_py(self.outputs, py, module, format)?;
+
+            Ok::<(PyObject, PyObject, PyObject, PyObject, PyObject), PyErr>((
+                blocks,
+                transactions,
+                receipts,
+                inputs,
+                outputs,
+            ))
+        })?;
+
+        compose_pyarrow_response(
+            self.archive_height,
+            self.next_block,
+            self.total_execution_time,
+            blocks,
+            transactions,
+            receipts,
+            inputs,
+            outputs,
+        )
+        .context("build arrow response")
+        .map_err(decode_err)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 /// Construct response and centralize error mapping for calling function.
 fn compose_pyarrow_response(
@@ -375,36 +1107,102 @@ fn compose_pyarrow_response(
     })
 }
 
-/// Uses RecordBatchReader to convert vector of ArrayBatch to reader by c-interface
-/// and then crates table from this reader with method from_batches
-fn convert_batch_to_pyarrow_table<'py>(
-    py: Python<'py>,
-    pyarrow: &'py PyModule,
-    batches: Vec<ArrowBatch>,
-) -> PyResult<PyObject> {
-    if batches.is_empty() {
-        return Ok(py.None());
+/// Batches of a single table as struct arrays.
+struct PreparedTable {
+    field: Field,
+    arrays: Vec<Box<dyn Array>>,
+}
+
+impl PreparedTable {
+    fn new(batches: Vec<ArrowBatch>) -> Option<Self> {
+        let schema = batches.first()?.schema.fields.clone();
+        let field = Field::new("a", DataType::Struct(schema), true);
+
+        let arrays = batches
+            .into_iter()
+            .map(|batch| {
+                StructArray::new(field.data_type.clone(), batch.chunk.arrays().to_vec(), None)
+                    .boxed()
+            })
+            .collect();
+
+        Some(Self { field, arrays })
     }
 
-    let schema = batches.first().unwrap().schema.fields.clone();
-    let field = Field::new("a", DataType::Struct(schema), true);
+    /// Exports the struct arrays by the arrow c stream interface.
+    fn export(self) -> ffi::ArrowArrayStream {
+        let iter = Box::new(self.arrays.into_iter().map(Ok)) as _;
+        ffi::export_iterator(iter, self.field)
+    }
 
-    let mut data = vec![];
-    for batch in batches {
-        data.push(
-            StructArray::new(field.data_type.clone(), batch.chunk.arrays().to_vec(), None).boxed(),
-        );
+    /// Converts the table into a pyarrow table or a polars DataFrame, None if there is no data.
+    fn to_py<'py>(
+        table: Option<Self>,
+        py: Python<'py>,
+        module: &'py PyModule,
+        format: TableFormat,
+    ) -> PyResult<PyObject> {
+        let table = match table {
+            Some(table) => table,
+            None => return Ok(py.None()),
+        };
+
+        match format {
+            TableFormat::Pyarrow => table.into_pyarrow(py, module),
+            TableFormat::Polars => {
+                let stream = Py::new(py, ArrowCStream { table: Some(table) })?;
+                let df = module.getattr("DataFrame")?.call1((stream,))?;
+                Ok(df.to_object(py))
+            }
+        }
     }
 
-    let iter = Box::new(data.into_iter().map(Ok)) as _;
-    let stream = Box::new(ffi::export_iterator(iter, field));
-    let py_stream = pyarrow.getattr("RecordBatchReader")?.call_method1(
-        "_import_from_c",
-        ((&*stream as *const ffi::ArrowArrayStream) as Py_uintptr_t,),
-    )?;
-    let table = pyarrow
-        .getattr("Table")?
-        .call_method1("from_batches", (py_stream,))?;
+    /// Uses RecordBatchReader to convert the struct arrays to reader by c-interface
+    /// and then crates table from this reader with method from_batches
+    fn into_pyarrow<'py>(self, py: Python<'py>, pyarrow: &'py PyModule) -> PyResult<PyObject> {
+        let stream = Box::new(self.export());
+        let py_stream = pyarrow.getattr("RecordBatchReader")?.call_method1(
+            "_import_from_c",
+            ((&*stream as *const ffi::ArrowArrayStream) as Py_uintptr_t,),
+        )?;
+        let table = pyarrow
+            .getattr("Table")?
+            .call_method1("from_batches", (py_stream,))?;
+
+        Ok(table.to_object(py))
+    }
+}
 
-    Ok(table.to_object(py))
+/// A table that is handed over to polars by the arrow PyCapsule interface, so it is
+/// imported without going through pyarrow.
+#[pyclass]
+struct ArrowCStream {
+    table: Option<PreparedTable>,
+}
+
+/// Arrow c stream owned by a PyCapsule.
+#[repr(transparent)]
+struct CapsuleStream(ffi::ArrowArrayStream);
+
+// SAFETY: the stream is only touched by the consumer of the capsule, which holds the GIL.
+unsafe impl Send for CapsuleStream {}
+
+#[pymethods]
+impl ArrowCStream {
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &mut self,
+        py: Python<'py>,
+        requested_schema: Option<&PyAny>,
+    ) -> PyResult<&'py PyCapsule> {
+        // the schema of the stream is fixed by the query, requested casts are left to the consumer
+        let _ = requested_schema;
+        let table = self
+            .table
+            .take()
+            .ok_or_else(|| PyValueError::new_err("arrow stream was already consumed"))?;
+        let name = CString::new("arrow_array_stream").unwrap();
+
+        PyCapsule::new(py, CapsuleStream(table.export()), Some(name))
+    }
 }
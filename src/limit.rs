@@ -0,0 +1,205 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::config::Config;
+
+/// Limits the requests of a client and all of its clones, so scripts that start many
+/// requests at once don't get throttled by the source.
+#[derive(Debug)]
+pub struct RequestLimiter {
+    concurrency: Option<Semaphore>,
+    rate: Option<TokenBucket>,
+}
+
+impl RequestLimiter {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let concurrency = match config.max_concurrent_requests {
+            Some(0) => return Err(anyhow!("max_concurrent_requests must be greater than zero")),
+            Some(max) => Some(Semaphore::new(max)),
+            None => None,
+        };
+
+        let rate = match config.requests_per_second {
+            Some(rate) if !(rate > 0.0 && rate.is_finite()) => {
+                return Err(anyhow!("requests_per_second must be a positive number"))
+            }
+            Some(rate) => Some(TokenBucket::new(rate)),
+            None => None,
+        };
+
+        Ok(Self { concurrency, rate })
+    }
+
+    /// Runs the request once both limits allow it, the concurrency slot is held until it finishes.
+    pub async fn run<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let _permit = match &self.concurrency {
+            Some(semaphore) => Some(semaphore.acquire().await.context("acquire request slot")?),
+            None => None,
+        };
+
+        if let Some(rate) = &self.rate {
+            rate.take().await;
+        }
+
+        op().await
+    }
+}
+
+/// Token bucket that refills at the given rate and holds up to a second worth of tokens,
+/// so short bursts are allowed but the average rate is capped.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    async fn take(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+                state.refilled_at = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+
+                Duration::from_secs_f64((1.0 - state.tokens) / self.rate)
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn limiter(
+        max_concurrent_requests: Option<usize>,
+        requests_per_second: Option<f64>,
+    ) -> Result<RequestLimiter> {
+        RequestLimiter::from_config(&Config {
+            max_concurrent_requests,
+            requests_per_second,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn validate_config() {
+        assert!(limiter(None, None).is_ok());
+        assert!(limiter(Some(1), Some(0.5)).is_ok());
+        assert!(limiter(Some(0), None).is_err());
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(limiter(None, Some(rate)).is_err(), "{}", rate);
+        }
+    }
+
+    #[tokio::test]
+    async fn bucket_allows_a_burst() {
+        let bucket = TokenBucket::new(10.0);
+
+        let start = Instant::now();
+        for _ in 0..10 {
+            bucket.take().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // the bucket is empty, the next token takes a tenth of a second to refill
+        bucket.take().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn bucket_holds_at_least_one_token() {
+        let bucket = TokenBucket::new(0.25);
+        assert_eq!(bucket.capacity, 1.0);
+
+        bucket.take().await;
+        let state = bucket.state.lock().await;
+        assert!(state.tokens < 1.0);
+    }
+
+    #[tokio::test]
+    async fn bucket_refills_up_to_capacity() {
+        let bucket = TokenBucket::new(5.0);
+        for _ in 0..5 {
+            bucket.take().await;
+        }
+
+        // an idle minute refills the bucket, but not beyond a second worth of tokens
+        bucket.state.lock().await.refilled_at -= Duration::from_secs(60);
+        bucket.take().await;
+
+        let state = bucket.state.lock().await;
+        assert!((state.tokens - 4.0).abs() < 1e-6, "{}", state.tokens);
+    }
+
+    #[tokio::test]
+    async fn cap_concurrent_requests() {
+        let limiter = Arc::new(limiter(Some(2), None).unwrap());
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let tasks = (0..8)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let running = running.clone();
+                let max_running = max_running.clone();
+                tokio::spawn(async move {
+                    limiter
+                        .run(|| async {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            max_running.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(())
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,111 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pyo3::prelude::*;
+
+/// Name of the python logger the records are forwarded to.
+const LOGGER: &str = "hyperfuel";
+
+/// Target of the records the tracing crate emits for the lifecycle of its spans.
+const TRACING_SPAN_TARGET: &str = "tracing::span";
+
+/// Forwards the log records of this crate and the hyperfuel_* crates it depends on to python's
+/// logging module, so they follow the logging configuration of the application.
+///
+/// Records of a target like 'hyperfuel_client::client' go to the 'hyperfuel.hyperfuel_client.client'
+/// logger, the ones of this crate to 'hyperfuel.<module>'. Records of other crates are dropped.
+struct PyLogger;
+
+impl PyLogger {
+    /// The target a record is forwarded under, records of spans are attributed to the module
+    /// that created the span.
+    fn source<'a>(record: &'a Record) -> Option<&'a str> {
+        let target = record.target();
+        if target.starts_with(LOGGER) {
+            return Some(target);
+        }
+
+        record
+            .module_path()
+            .filter(|module| target.starts_with(TRACING_SPAN_TARGET) && module.starts_with(LOGGER))
+    }
+
+    fn logger_name(target: &str) -> String {
+        let name = target.replace("::", ".");
+        if target == LOGGER || target.starts_with("hyperfuel::") {
+            name
+        } else {
+            format!("{}.{}", LOGGER, name)
+        }
+    }
+
+    /// Python logging level of a record, trace records use level 5 since python has no TRACE.
+    fn py_level(level: Level) -> u8 {
+        match level {
+            Level::Error => 40,
+            Level::Warn => 30,
+            Level::Info => 20,
+            Level::Debug => 10,
+            Level::Trace => 5,
+        }
+    }
+
+    fn forward(py: Python, source: &str, record: &Record) -> PyResult<()> {
+        let logging = py.import("logging")?;
+        let name = Self::logger_name(source);
+        let logger = logging.call_method1("getLogger", (&name,))?;
+
+        let level = Self::py_level(record.level());
+        if !logger.call_method1("isEnabledFor", (level,))?.is_true()? {
+            return Ok(());
+        }
+
+        // the record points to the rust source, the message is passed without args so '%' isn't interpreted
+        let py_record = logger.call_method1(
+            "makeRecord",
+            (
+                &name,
+                level,
+                record.file().unwrap_or("<unknown>"),
+                record.line().unwrap_or(0),
+                record.args().to_string(),
+                (),
+                py.None(),
+            ),
+        )?;
+        logger.call_method1("handle", (py_record,))?;
+
+        Ok(())
+    }
+}
+
+impl Log for PyLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        target.starts_with(LOGGER) || target.starts_with(TRACING_SPAN_TARGET)
+    }
+
+    fn log(&self, record: &Record) {
+        let source = match Self::source(record) {
+            Some(source) => source,
+            None => return,
+        };
+
+        // records can still come in from runtime threads while the interpreter shuts down
+        if unsafe { pyo3::ffi::Py_IsInitialized() } == 0 {
+            return;
+        }
+
+        Python::with_gil(|py| {
+            // handlers report their own errors, a failure here can't be logged anywhere else
+            Self::forward(py, source, record).ok();
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the bridge as the global logger, it is a no-op if a logger is already installed.
+pub fn init() {
+    if log::set_boxed_logger(Box::new(PyLogger)).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use arrow2::compute::aggregate::estimated_bytes_size;
+use pyo3::prelude::*;
+
+/// Upper bounds of the request latency histogram buckets in milliseconds.
+const LATENCY_BUCKETS_MILLIS: [u64; 13] = [
+    5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000,
+];
+
+/// A response that carries values that are aggregated in the metrics.
+pub trait Measured {
+    /// Time the source took to execute the query in milliseconds.
+    fn server_execution_time(&self) -> u64 {
+        0
+    }
+
+    /// Estimated in-memory size of the decoded arrow data in bytes.
+    fn arrow_bytes(&self) -> u64 {
+        0
+    }
+}
+
+impl Measured for u64 {}
+
+impl Measured for hyperfuel_client::QueryResponse {
+    fn server_execution_time(&self) -> u64 {
+        self.total_execution_time
+    }
+
+    /// The transport doesn't expose the size on the wire, so the buffers of the decoded
+    /// arrays are measured instead.
+    fn arrow_bytes(&self) -> u64 {
+        let data = &self.data;
+        [
+            &data.blocks,
+            &data.transactions,
+            &data.receipts,
+            &data.inputs,
+            &data.outputs,
+        ]
+        .into_iter()
+        .flatten()
+        .flat_map(|batch| batch.chunk.arrays())
+        .map(|array| estimated_bytes_size(array.as_ref()) as u64)
+        .sum()
+    }
+}
+
+impl Measured for hyperfuel_client::QueryResponseTyped {
+    fn server_execution_time(&self) -> u64 {
+        self.total_execution_time
+    }
+}
+
+impl Measured for hyperfuel_client::LogResponse {
+    fn server_execution_time(&self) -> u64 {
+        self.total_execution_time
+    }
+}
+
+/// Request metrics of a client, shared by all of its clones.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    methods: Mutex<BTreeMap<&'static str, MethodMetrics>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct MethodMetrics {
+    requests: u64,
+    errors: u64,
+    retries: u64,
+    arrow_bytes: u64,
+    server_execution_time_millis: u64,
+    latency_sum: Duration,
+    /// Number of requests per latency bucket, the last one counts the requests above all bounds
+    latency_buckets: [u64; LATENCY_BUCKETS_MILLIS.len() + 1],
+}
+
+impl Metrics {
+    /// Records a finished request, including all of its retries.
+    pub fn record<T: Measured>(
+        &self,
+        method: &'static str,
+        latency: Duration,
+        retries: u64,
+        res: &Result<T>,
+    ) {
+        let mut methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        let metrics = methods.entry(method).or_default();
+
+        metrics.requests += 1;
+        metrics.retries += retries;
+        metrics.latency_sum += latency;
+
+        let millis = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MILLIS
+            .iter()
+            .position(|&bound| millis <= u128::from(bound))
+            .unwrap_or(LATENCY_BUCKETS_MILLIS.len());
+        metrics.latency_buckets[bucket] += 1;
+
+        match res {
+            Ok(res) => {
+                metrics.arrow_bytes += res.arrow_bytes();
+                metrics.server_execution_time_millis += res.server_execution_time();
+            }
+            Err(_) => metrics.errors += 1,
+        }
+    }
+
+    pub fn snapshot(&self) -> ClientStats {
+        let methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+
+        let methods = methods
+            .iter()
+            .map(|(&method, metrics)| {
+                let mut count = 0;
+                let latency_buckets = LATENCY_BUCKETS_MILLIS
+                    .iter()
+                    .zip(metrics.latency_buckets)
+                    .map(|(&bound, n)| {
+                        count += n;
+                        (bound, count)
+                    })
+                    .collect();
+
+                let stats = MethodStats {
+                    requests: metrics.requests,
+                    errors: metrics.errors,
+                    retries: metrics.retries,
+                    arrow_bytes: metrics.arrow_bytes,
+                    server_execution_time_millis: metrics.server_execution_time_millis,
+                    latency_sum_millis: metrics.latency_sum.as_secs_f64() * 1_000.0,
+                    latency_buckets,
+                };
+                (method.to_owned(), stats)
+            })
+            .collect();
+
+        ClientStats { methods }
+    }
+}
+
+/// A counter of the Prometheus output as (name, help text, value of the method).
+type Counter = (&'static str, &'static str, fn(&MethodStats) -> u64);
+
+/// Request metrics of a client, keyed by the method of the request
+/// ("get_height", "get_data", "get_selected_data", "get_arrow_data" or "preset_query_get_logs").
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Clone)]
+pub struct ClientStats {
+    pub methods: BTreeMap<String, MethodStats>,
+}
+
+/// Metrics of the requests of a single method.
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Clone)]
+pub struct MethodStats {
+    /// Number of requests, a request and all of its retries count once
+    pub requests: u64,
+    /// Number of requests that failed after all retries
+    pub errors: u64,
+    /// Number of retried attempts
+    pub retries: u64,
+    /// Estimated in-memory size of the arrow data of the responses in bytes, not the size on the
+    /// wire. Only get_arrow_data responses carry arrow data, it is zero for the other methods.
+    pub arrow_bytes: u64,
+    /// Sum of the execution times the source reported in milliseconds
+    pub server_execution_time_millis: u64,
+    /// Sum of the request latencies in milliseconds, including retries and waiting for the request limits
+    pub latency_sum_millis: f64,
+    /// Latency histogram as (upper bound in milliseconds, number of requests that took at most that long)
+    pub latency_buckets: Vec<(u64, u64)>,
+}
+
+#[pymethods]
+impl ClientStats {
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let counters: [Counter; 5] = [
+            (
+                "hyperfuel_requests_total",
+                "Requests sent to the source.",
+                |s| s.requests,
+            ),
+            (
+                "hyperfuel_request_errors_total",
+                "Requests that failed after all retries.",
+                |s| s.errors,
+            ),
+            (
+                "hyperfuel_request_retries_total",
+                "Retried request attempts.",
+                |s| s.retries,
+            ),
+            (
+                "hyperfuel_arrow_data_bytes_total",
+                "Estimated in-memory size of the arrow data of the responses in bytes.",
+                |s| s.arrow_bytes,
+            ),
+            (
+                "hyperfuel_server_execution_time_milliseconds_total",
+                "Query execution time reported by the source in milliseconds.",
+                |s| s.server_execution_time_millis,
+            ),
+        ];
+
+        for (name, help, value) in counters {
+            writeln!(out, "# HELP {} {}", name, help).unwrap();
+            writeln!(out, "# TYPE {} counter", name).unwrap();
+            for (method, stats) in &self.methods {
+                writeln!(out, "{}{{method=\"{}\"}} {}", name, method, value(stats)).unwrap();
+            }
+        }
+
+        let name = "hyperfuel_request_duration_seconds";
+        writeln!(out, "# HELP {} Request latency in seconds.", name).unwrap();
+        writeln!(out, "# TYPE {} histogram", name).unwrap();
+        for (method, stats) in &self.methods {
+            for (bound, count) in &stats.latency_buckets {
+                writeln!(
+                    out,
+                    "{}_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    name,
+                    method,
+                    *bound as f64 / 1_000.0,
+                    count
+                )
+                .unwrap();
+            }
+            writeln!(
+                out,
+                "{}_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                name, method, stats.requests
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "{}_sum{{method=\"{}\"}} {}",
+                name,
+                method,
+                stats.latency_sum_millis / 1_000.0
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "{}_count{{method=\"{}\"}} {}",
+                name, method, stats.requests
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pymethods]
+impl MethodStats {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn record(metrics: &Metrics, method: &'static str, millis: u64, res: Result<u64>) {
+        metrics.record(method, Duration::from_millis(millis), 0, &res);
+    }
+
+    #[test]
+    fn count_requests_and_errors() {
+        let metrics = Metrics::default();
+        record(&metrics, "get_height", 3, Ok(1));
+        record(&metrics, "get_height", 7, Err(anyhow!("failed")));
+        metrics.record("get_height", Duration::from_millis(2), 3, &Ok(1u64));
+        record(&metrics, "get_data", 1, Ok(1));
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.methods.len(), 2);
+
+        let height = &stats.methods["get_height"];
+        assert_eq!(height.requests, 3);
+        assert_eq!(height.errors, 1);
+        assert_eq!(height.retries, 3);
+        assert_eq!(height.arrow_bytes, 0);
+        assert_eq!(height.latency_sum_millis, 12.0);
+
+        assert_eq!(stats.methods["get_data"].requests, 1);
+        assert_eq!(stats.methods["get_data"].errors, 0);
+    }
+
+    #[test]
+    fn cumulative_latency_buckets() {
+        let metrics = Metrics::default();
+        for millis in [1, 5, 6, 30, 100_000] {
+            record(&metrics, "get_height", millis, Ok(1));
+        }
+
+        let buckets = &metrics.snapshot().methods["get_height"].latency_buckets;
+        assert_eq!(buckets.len(), LATENCY_BUCKETS_MILLIS.len());
+        assert_eq!(buckets[0], (5, 2));
+        assert_eq!(buckets[1], (10, 3));
+        assert_eq!(buckets[2], (25, 3));
+        assert_eq!(buckets[3], (50, 4));
+        // the request above all bounds is only counted in the total
+        assert_eq!(buckets.last(), Some(&(60_000, 4)));
+    }
+
+    #[test]
+    fn prometheus_output() {
+        let metrics = Metrics::default();
+        record(&metrics, "get_height", 4, Ok(1));
+        record(&metrics, "get_height", 70_000, Err(anyhow!("failed")));
+
+        let out = metrics.snapshot().to_prometheus();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        for line in [
+            "# TYPE hyperfuel_requests_total counter",
+            "hyperfuel_requests_total{method=\"get_height\"} 2",
+            "hyperfuel_request_errors_total{method=\"get_height\"} 1",
+            "hyperfuel_arrow_data_bytes_total{method=\"get_height\"} 0",
+            "# TYPE hyperfuel_request_duration_seconds histogram",
+            "hyperfuel_request_duration_seconds_bucket{method=\"get_height\",le=\"0.005\"} 1",
+            "hyperfuel_request_duration_seconds_bucket{method=\"get_height\",le=\"60\"} 1",
+            "hyperfuel_request_duration_seconds_bucket{method=\"get_height\",le=\"+Inf\"} 2",
+            "hyperfuel_request_duration_seconds_sum{method=\"get_height\"} 70.004",
+            "hyperfuel_request_duration_seconds_count{method=\"get_height\"} 2",
+        ] {
+            assert!(lines.contains(&line), "missing {:?} in\n{}", line, out);
+        }
+
+        let buckets = lines
+            .iter()
+            .filter(|line| line.starts_with("hyperfuel_request_duration_seconds_bucket"))
+            .count();
+        assert_eq!(buckets, LATENCY_BUCKETS_MILLIS.len() + 1);
+    }
+}
@@ -0,0 +1,218 @@
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use hyperfuel_client::{QueryResponse, QueryResponseData};
+use hyperfuel_net_types::Query;
+use tokio::sync::mpsc;
+
+use crate::query::ValidationError;
+use crate::stream::{end_block, send_ranges, Page};
+use crate::HyperfuelClient;
+
+pub const DEFAULT_NUM_WORKERS: usize = 4;
+pub const DEFAULT_CHUNK_BLOCKS: u64 = 100_000;
+
+/// Parsed settings of a parallel fetch.
+#[derive(Clone, Copy, Debug)]
+pub struct ParallelOptions {
+    /// Number of chunks that are fetched at the same time
+    pub num_workers: usize,
+    /// Number of blocks in each chunk
+    pub chunk_blocks: u64,
+}
+
+impl ParallelOptions {
+    pub fn new(num_workers: usize, chunk_blocks: u64) -> Result<Self, ValidationError> {
+        if num_workers == 0 {
+            return Err(ValidationError::new(
+                "num_workers",
+                "must be greater than zero",
+            ));
+        }
+        if chunk_blocks == 0 {
+            return Err(ValidationError::new(
+                "chunk_blocks",
+                "must be greater than zero",
+            ));
+        }
+
+        Ok(Self {
+            num_workers,
+            chunk_blocks,
+        })
+    }
+}
+
+/// Fetches the [query.from_block, to_block) range as chunks of `chunk_blocks` blocks.
+///
+/// Up to `num_workers` chunks are fetched at the same time, each with its own pagination loop,
+/// and the pages are yielded in block order as they arrive, see `stream::send_ranges`.
+/// If `partition_blocks` is given, chunks never cross a multiple of it so no page spans
+/// two partitions. `fetch` makes the request of a single page.
+pub fn fetch_pages<P, F, Fut>(
+    client: HyperfuelClient,
+    query: Query,
+    to_block: u64,
+    options: ParallelOptions,
+    partition_blocks: Option<u64>,
+    fetch: F,
+) -> BoxStream<'static, Result<P>>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<P>> + Send + 'static,
+{
+    let ranges = chunk_ranges(
+        query.from_block,
+        to_block,
+        options.chunk_blocks,
+        partition_blocks,
+    );
+
+    let (tx, rx) = mpsc::channel(options.num_workers);
+
+    pyo3_asyncio::tokio::get_runtime().spawn(async move {
+        tokio::select! {
+            _ = tx.closed() => (),
+            res = send_ranges(&client, &query, ranges, options.num_workers, &fetch, &tx) => {
+                if let Err(e) = res {
+                    tx.send(Err(e)).await.ok();
+                }
+            }
+        }
+    });
+
+    futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|page| (page, rx))
+    })
+    .boxed()
+}
+
+/// Runs the query up to its to_block, or up to and including the height of the source if it
+/// has none, and merges all pages into a single response in block order.
+pub async fn collect(
+    client: &HyperfuelClient,
+    query: Query,
+    options: ParallelOptions,
+) -> Result<QueryResponse> {
+    let height = client
+        .fetch_height()
+        .await
+        .context("get height of source")?;
+
+    let to_block = end_block(query.to_block, height);
+
+    let mut merged = QueryResponse {
+        archive_height: Some(height),
+        next_block: std::cmp::max(query.from_block, to_block),
+        total_execution_time: 0,
+        data: QueryResponseData {
+            blocks: Vec::new(),
+            transactions: Vec::new(),
+            receipts: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        },
+    };
+
+    let fetch = |client: HyperfuelClient, query: Query| async move {
+        client.fetch_arrow_data(&query).await
+    };
+    let mut pages = fetch_pages(client.clone(), query, to_block, options, None, fetch);
+    while let Some(page) = pages.try_next().await.context("run query")? {
+        merged.archive_height = std::cmp::max(merged.archive_height, page.archive_height);
+        merged.next_block = page.next_block;
+        merged.total_execution_time += page.total_execution_time;
+
+        let data = page.data;
+        merged.data.blocks.extend(data.blocks);
+        merged.data.transactions.extend(data.transactions);
+        merged.data.receipts.extend(data.receipts);
+        merged.data.inputs.extend(data.inputs);
+        merged.data.outputs.extend(data.outputs);
+    }
+
+    Ok(merged)
+}
+
+/// Splits the range into chunks, starting a new chunk at every multiple of `partition_blocks`.
+fn chunk_ranges(
+    from_block: u64,
+    to_block: u64,
+    chunk_blocks: u64,
+    partition_blocks: Option<u64>,
+) -> Vec<(u64, u64)> {
+    let mut ranges = Vec::new();
+    let mut start = from_block;
+    while start < to_block {
+        let mut end = std::cmp::min(start.saturating_add(chunk_blocks), to_block);
+        if let Some(blocks) = partition_blocks {
+            let partition_end = (start / blocks).saturating_add(1).saturating_mul(blocks);
+            end = std::cmp::min(end, partition_end);
+        }
+        ranges.push((start, end));
+        start = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_options() {
+        assert!(ParallelOptions::new(1, 1).is_ok());
+        assert_eq!(ParallelOptions::new(0, 1).unwrap_err().field, "num_workers");
+        assert_eq!(
+            ParallelOptions::new(1, 0).unwrap_err().field,
+            "chunk_blocks"
+        );
+    }
+
+    #[test]
+    fn chunk_range() {
+        assert_eq!(
+            chunk_ranges(0, 250, 100, None),
+            vec![(0, 100), (100, 200), (200, 250)]
+        );
+        assert_eq!(chunk_ranges(5, 105, 100, None), vec![(5, 105)]);
+        assert_eq!(chunk_ranges(0, 50, 100, None), vec![(0, 50)]);
+    }
+
+    #[test]
+    fn chunk_empty_range() {
+        assert!(chunk_ranges(10, 10, 100, None).is_empty());
+        assert!(chunk_ranges(20, 10, 100, None).is_empty());
+        assert!(chunk_ranges(10, 10, 100, Some(7)).is_empty());
+    }
+
+    #[test]
+    fn chunk_at_partitions() {
+        assert_eq!(
+            chunk_ranges(50, 320, 100, Some(100)),
+            vec![(50, 100), (100, 200), (200, 300), (300, 320)]
+        );
+        assert_eq!(
+            chunk_ranges(0, 100, 30, Some(50)),
+            vec![(0, 30), (30, 50), (50, 80), (80, 100)]
+        );
+        assert_eq!(
+            chunk_ranges(0, 25, 100, Some(10)),
+            vec![(0, 10), (10, 20), (20, 25)]
+        );
+    }
+
+    #[test]
+    fn chunk_up_to_max_block() {
+        assert_eq!(
+            chunk_ranges(u64::MAX - 150, u64::MAX, 100, None),
+            vec![(u64::MAX - 150, u64::MAX - 50), (u64::MAX - 50, u64::MAX)]
+        );
+        assert_eq!(
+            chunk_ranges(u64::MAX - 10, u64::MAX, 100, Some(1000)),
+            vec![(u64::MAX - 10, u64::MAX)]
+        );
+    }
+}
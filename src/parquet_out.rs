@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use arrow2::{
+    datatypes::Schema,
+    io::parquet::write::{transverse, CompressionOptions, Encoding, FileSink, WriteOptions},
+};
+use futures::SinkExt;
+use hyperfuel_net_types::Query;
+use pyo3::PyObject;
+use tokio::fs::File;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::column_mapping::{ColumnTypes, Mapping};
+use crate::config::ParquetOptions;
+use crate::export::{self, cast_tables, table_schemas, ExportFormat, ExportSummary, TABLES};
+use crate::parallel::ParallelOptions;
+use crate::HyperfuelClient;
+
+pub async fn create_parquet_folder(
+    client: &HyperfuelClient,
+    query: Query,
+    path: String,
+    mapping: &Mapping,
+    options: &ParquetOptions,
+    parallel: Option<ParallelOptions>,
+    progress: Option<PyObject>,
+) -> Result<ExportSummary> {
+    let format = Parquet {
+        schemas: table_schemas(&query, mapping)?,
+        mapping,
+        compression: options.compression,
+    };
+
+    export::run(
+        client,
+        query,
+        path,
+        &format,
+        &options.export,
+        parallel,
+        progress,
+    )
+    .await
+}
+
+/// Writes the tables of an export to parquet files.
+struct Parquet<'a> {
+    /// Schemas of the tables after the column mapping
+    schemas: [Schema; 5],
+    mapping: &'a Mapping,
+    compression: CompressionOptions,
+}
+
+impl ExportFormat for Parquet<'_> {
+    type Page = hyperfuel_client::QueryResponse;
+    type Part = Vec<FileSink<'static, Compat<File>>>;
+
+    fn extension(&self) -> &'static str {
+        "parquet"
+    }
+
+    fn column_types(&self) -> ColumnTypes {
+        self.mapping.column_types()
+    }
+
+    async fn create(&self, files: Vec<PathBuf>) -> Result<Self::Part> {
+        let mut sinks = Vec::with_capacity(TABLES.len());
+        for ((table, schema), file) in TABLES.iter().zip(self.schemas.iter()).zip(files) {
+            let sink = make_file_sink(&file, schema.clone(), self.compression)
+                .await
+                .with_context(|| format!("create {} output parquet", table))?;
+            sinks.push(sink);
+        }
+
+        Ok(sinks)
+    }
+
+    async fn write(&self, part: &mut Self::Part, page: Self::Page) -> Result<()> {
+        let tables = cast_tables(page.data, self.mapping)?;
+        for ((table, sink), batches) in TABLES.iter().zip(part.iter_mut()).zip(tables) {
+            for batch in batches {
+                sink.send(batch.chunk)
+                    .await
+                    .with_context(|| format!("write {} chunk to parquet", table))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&self, part: Self::Part) -> Result<()> {
+        for (table, mut sink) in TABLES.iter().zip(part) {
+            sink.close()
+                .await
+                .with_context(|| format!("finish writing {} parquet", table))?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn make_file_sink(
+    path: &Path,
+    schema: Schema,
+    compression: CompressionOptions,
+) -> Result<FileSink<'static, Compat<File>>> {
+    let file = tokio::fs::File::create(path)
+        .await
+        .context("create parquet file")?
+        .compat_write();
+
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|f| transverse(&f.data_type, |_| Encoding::Plain))
+        .collect();
+
+    let file_sink = FileSink::try_new(
+        file,
+        schema,
+        encodings,
+        WriteOptions {
+            write_statistics: true,
+            version: arrow2::io::parquet::write::Version::V2,
+            compression,
+            data_pagesize_limit: None,
+        },
+    )
+    .context("create file sink")?;
+
+    Ok(file_sink)
+}
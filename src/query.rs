@@ -1,4 +1,8 @@
+use std::fmt;
+
 use anyhow::{Context, Result};
+use arrow2::datatypes::Schema;
+use hyperfuel_format::{InputType, OutputType, ReceiptType, TransactionStatus, TransactionType};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -153,6 +157,7 @@ pub struct Query {
     pub include_all_blocks: Option<bool>,
     /// Field selection. The user can select which fields they are interested in, requesting less fields will improve
     ///  query execution time and reduce the payload size so the user should always use a minimal number of fields.
+    #[serde(default)]
     pub field_selection: FieldSelection,
     /// Maximum number of blocks that should be returned, the server might return more blocks than this number but
     ///  it won't overshoot by too much.
@@ -166,9 +171,365 @@ pub struct Query {
 
 impl Query {
     pub fn try_convert(&self) -> Result<hyperfuel_net_types::Query> {
+        self.validate()?;
+
         let json = serde_json::to_vec(self).context("serialize to json")?;
         serde_json::from_slice(&json).context("parse json")
     }
+
+    /// Reads a query from text, e.g. a query definition in a config file.
+    ///
+    /// Accepts the JSON format of hyperfuel_net_types::Query as written by to_json or by Rust
+    /// services, and the same structure in YAML or TOML.
+    pub fn from_text(text: &str, format: QueryFormat) -> Result<Self> {
+        let query: Self = match format {
+            QueryFormat::Json => serde_json::from_str(text).context("parse json")?,
+            QueryFormat::Yaml => serde_yaml::from_str(text).context("parse yaml")?,
+            QueryFormat::Toml => toml::from_str(text).context("parse toml")?,
+        };
+
+        query.try_convert()?;
+
+        Ok(query)
+    }
+
+    /// Serializes the query in the JSON format of hyperfuel_net_types::Query.
+    pub fn to_json(&self, pretty: bool) -> Result<String> {
+        let query = self.try_convert()?;
+
+        if pretty {
+            serde_json::to_string_pretty(&query).context("serialize query")
+        } else {
+            serde_json::to_string(&query).context("serialize query")
+        }
+    }
+
+    /// Checks the query for invalid values before it is sent to the server.
+    ///
+    /// The returned error carries the path of the first offending value, e.g. `receipts[2].asset_id[0]`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_block_range()?;
+        self.validate_selections()
+    }
+
+    /// Checks that the block range isn't empty.
+    pub fn validate_block_range(&self) -> Result<(), ValidationError> {
+        if let Some(to_block) = self.to_block {
+            if to_block <= self.from_block {
+                return Err(ValidationError::new(
+                    "to_block",
+                    format!(
+                        "to_block ({}) must be greater than from_block ({})",
+                        to_block, self.from_block
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the ids, enum values and field names of the selections.
+    pub fn validate_selections(&self) -> Result<(), ValidationError> {
+        for (i, selection) in self.receipts.iter().flatten().enumerate() {
+            let path = format!("receipts[{}]", i);
+            check_hashes(&path, "root_contract_id", &selection.root_contract_id)?;
+            check_hashes(&path, "to_address", &selection.to_address)?;
+            check_hashes(&path, "asset_id", &selection.asset_id)?;
+            check_enum(&path, "receipt_type", &selection.receipt_type, |v| {
+                ReceiptType::from_u8(v).is_ok()
+            })?;
+            check_hashes(&path, "sender", &selection.sender)?;
+            check_hashes(&path, "recipient", &selection.recipient)?;
+            check_hashes(&path, "contract_id", &selection.contract_id)?;
+            check_tx_status(&path, &selection.tx_status)?;
+            check_tx_type(&path, &selection.tx_type)?;
+        }
+
+        for (i, selection) in self.inputs.iter().flatten().enumerate() {
+            let path = format!("inputs[{}]", i);
+            check_hashes(&path, "owner", &selection.owner)?;
+            check_hashes(&path, "asset_id", &selection.asset_id)?;
+            check_hashes(&path, "contract", &selection.contract)?;
+            check_hashes(&path, "sender", &selection.sender)?;
+            check_hashes(&path, "recipient", &selection.recipient)?;
+            check_enum(&path, "input_type", &selection.input_type, |v| {
+                InputType::from_u8(v).is_ok()
+            })?;
+            check_tx_status(&path, &selection.tx_status)?;
+            check_tx_type(&path, &selection.tx_type)?;
+        }
+
+        for (i, selection) in self.outputs.iter().flatten().enumerate() {
+            let path = format!("outputs[{}]", i);
+            check_hashes(&path, "to", &selection.to)?;
+            check_hashes(&path, "asset_id", &selection.asset_id)?;
+            check_hashes(&path, "contract", &selection.contract)?;
+            check_enum(&path, "output_type", &selection.output_type, |v| {
+                OutputType::from_u8(v).is_ok()
+            })?;
+            check_tx_status(&path, &selection.tx_status)?;
+            check_tx_type(&path, &selection.tx_type)?;
+        }
+
+        let fields = &self.field_selection;
+        let path = "field_selection";
+        check_fields(
+            path,
+            "block",
+            &fields.block,
+            &hyperfuel_schema::block_header(),
+        )?;
+        check_fields(
+            path,
+            "transaction",
+            &fields.transaction,
+            &hyperfuel_schema::transaction(),
+        )?;
+        check_fields(
+            path,
+            "receipt",
+            &fields.receipt,
+            &hyperfuel_schema::receipt(),
+        )?;
+        check_fields(path, "input", &fields.input, &hyperfuel_schema::input())?;
+        check_fields(path, "output", &fields.output, &hyperfuel_schema::output())?;
+
+        Ok(())
+    }
+}
+
+/// Text format of a query definition.
+#[derive(Debug, Clone, Copy)]
+pub enum QueryFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl QueryFormat {
+    pub fn from_name(name: &str) -> Result<Self, ValidationError> {
+        match name {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            _ => Err(ValidationError::new(
+                "format",
+                format!("unknown query format \"{}\"", name),
+            )),
+        }
+    }
+}
+
+/// Query for the Log and LogData receipts emitted by a set of contracts.
+#[derive(Default, Clone, Debug, dict_derive::FromPyObject, dict_derive::IntoPyObject)]
+pub struct LogQuery {
+    /// The block to start the query from
+    pub from_block: u64,
+    /// The block to end the query at, exclusive. If not specified, the query will go until the
+    ///  end of data.
+    pub to_block: Option<u64>,
+    /// Contracts that emitted the logs (root_contract_id of the receipt)
+    pub contracts: Vec<String>,
+    /// Log ids to select (the rb register of the receipt), all logs are returned if not specified
+    pub rb: Option<Vec<u64>>,
+    /// Receipt types to select, Log and/or LogData. Both are returned if not specified
+    pub receipt_type: Option<Vec<u8>>,
+    /// Also return logs of failed transactions, default is false
+    pub include_failed: Option<bool>,
+}
+
+impl LogQuery {
+    pub fn try_convert(&self) -> Result<hyperfuel_net_types::Query> {
+        self.validate()?;
+
+        let receipt_type = self
+            .receipt_type
+            .clone()
+            .unwrap_or_else(|| vec![ReceiptType::Log.to_u8(), ReceiptType::LogData.to_u8()]);
+        let tx_status = if self.include_failed.unwrap_or(false) {
+            vec![
+                TransactionStatus::Success.as_u8(),
+                TransactionStatus::Failure.as_u8(),
+            ]
+        } else {
+            vec![TransactionStatus::Success.as_u8()]
+        };
+
+        let query = Query {
+            from_block: self.from_block,
+            to_block: self.to_block,
+            receipts: Some(vec![ReceiptSelection {
+                root_contract_id: Some(self.contracts.clone()),
+                receipt_type: Some(receipt_type),
+                rb: self.rb.clone(),
+                tx_status: Some(tx_status),
+                ..Default::default()
+            }]),
+            field_selection: FieldSelection {
+                receipt: Some(LOG_FIELDS.iter().map(|f| f.to_string()).collect()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        query.try_convert()
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(to_block) = self.to_block {
+            if to_block <= self.from_block {
+                return Err(ValidationError::new(
+                    "to_block",
+                    format!(
+                        "to_block ({}) must be greater than from_block ({})",
+                        to_block, self.from_block
+                    ),
+                ));
+            }
+        }
+
+        if self.contracts.is_empty() {
+            return Err(ValidationError::new(
+                "contracts",
+                "at least one contract is required",
+            ));
+        }
+
+        check_hashes("", "contracts", &Some(self.contracts.clone()))?;
+        check_enum("", "receipt_type", &self.receipt_type, |v| {
+            v == ReceiptType::Log.to_u8() || v == ReceiptType::LogData.to_u8()
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Receipt fields needed to decode a log, plus some context.
+const LOG_FIELDS: &[&str] = &[
+    "block_height",
+    "tx_id",
+    "tx_status",
+    "receipt_index",
+    "receipt_type",
+    "contract_id",
+    "root_contract_id",
+    "ra",
+    "rb",
+    "rc",
+    "rd",
+    "pc",
+    "is",
+    "ptr",
+    "len",
+    "digest",
+    "data",
+];
+
+/// An invalid value in a query.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// Path of the offending value, e.g. `receipts[2].asset_id[0]`
+    pub field: String,
+    pub msg: String,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, msg: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value at {}: {}", self.field, self.msg)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+fn field_path(path: &str, name: &str, i: usize) -> String {
+    if path.is_empty() {
+        format!("{}[{}]", name, i)
+    } else {
+        format!("{}.{}[{}]", path, name, i)
+    }
+}
+
+// ids, addresses and asset ids are all 32 bytes
+fn check_hashes(
+    path: &str,
+    name: &str,
+    values: &Option<Vec<String>>,
+) -> Result<(), ValidationError> {
+    for (i, value) in values.iter().flatten().enumerate() {
+        let hex = value.strip_prefix("0x").ok_or_else(|| {
+            ValidationError::new(
+                field_path(path, name, i),
+                format!("\"{}\" doesn't start with \"0x\"", value),
+            )
+        })?;
+
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ValidationError::new(
+                field_path(path, name, i),
+                format!("\"{}\" is not a 32 byte hex string", value),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_enum(
+    path: &str,
+    name: &str,
+    values: &Option<Vec<u8>>,
+    is_known: impl Fn(u8) -> bool,
+) -> Result<(), ValidationError> {
+    for (i, value) in values.iter().flatten().enumerate() {
+        if !is_known(*value) {
+            return Err(ValidationError::new(
+                field_path(path, name, i),
+                format!("unknown {} {}", name, value),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_tx_status(path: &str, values: &Option<Vec<u8>>) -> Result<(), ValidationError> {
+    check_enum(path, "tx_status", values, |v| {
+        TransactionStatus::from_u8(v).is_ok()
+    })
+}
+
+fn check_tx_type(path: &str, values: &Option<Vec<u8>>) -> Result<(), ValidationError> {
+    check_enum(path, "tx_type", values, |v| {
+        TransactionType::from_u8(v).is_ok()
+    })
+}
+
+fn check_fields(
+    path: &str,
+    name: &str,
+    values: &Option<Vec<String>>,
+    schema: &Schema,
+) -> Result<(), ValidationError> {
+    for (i, value) in values.iter().flatten().enumerate() {
+        if !schema.fields.iter().any(|f| &f.name == value) {
+            return Err(ValidationError::new(
+                field_path(path, name, i),
+                format!("unknown {} field \"{}\"", name, value),
+            ));
+        }
+    }
+
+    Ok(())
 }
 
 impl TryFrom<hyperfuel_net_types::Query> for Query {
@@ -179,3 +540,137 @@ impl TryFrom<hyperfuel_net_types::Query> for Query {
         serde_json::from_slice(&json).context("parse json")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    #[test]
+    fn valid_query() {
+        let query = Query {
+            from_block: 10,
+            to_block: Some(20),
+            receipts: Some(vec![ReceiptSelection {
+                root_contract_id: Some(vec![HASH.to_owned()]),
+                receipt_type: Some(vec![ReceiptType::Log.to_u8()]),
+                ..Default::default()
+            }]),
+            field_selection: FieldSelection {
+                block: Some(vec!["height".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        query.validate().unwrap();
+    }
+
+    #[test]
+    fn empty_block_range() {
+        let query = Query {
+            from_block: 10,
+            to_block: Some(10),
+            ..Default::default()
+        };
+
+        let err = query.validate().unwrap_err();
+        assert_eq!(err.field, "to_block");
+    }
+
+    #[test]
+    fn invalid_hash_path() {
+        let query = Query {
+            receipts: Some(vec![
+                ReceiptSelection::default(),
+                ReceiptSelection::default(),
+                ReceiptSelection {
+                    asset_id: Some(vec![HASH.to_owned(), "0x1234".to_owned()]),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let err = query.validate().unwrap_err();
+        assert_eq!(err.field, "receipts[2].asset_id[1]");
+        assert_eq!(
+            err.to_string(),
+            "invalid value at receipts[2].asset_id[1]: \"0x1234\" is not a 32 byte hex string"
+        );
+    }
+
+    #[test]
+    fn missing_hex_prefix() {
+        let query = Query {
+            outputs: Some(vec![OutputSelection {
+                to: Some(vec![HASH[2..].to_owned()]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(query.validate().unwrap_err().field, "outputs[0].to[0]");
+    }
+
+    #[test]
+    fn unknown_enum_value() {
+        let query = Query {
+            receipts: Some(vec![ReceiptSelection {
+                receipt_type: Some(vec![ReceiptType::Log.to_u8(), u8::MAX]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            query.validate().unwrap_err().field,
+            "receipts[0].receipt_type[1]"
+        );
+    }
+
+    #[test]
+    fn unknown_field() {
+        let query = Query {
+            field_selection: FieldSelection {
+                transaction: Some(vec!["not_a_field".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            query.validate().unwrap_err().field,
+            "field_selection.transaction[0]"
+        );
+    }
+
+    #[test]
+    fn log_query() {
+        let query = LogQuery {
+            contracts: vec![HASH.to_owned()],
+            ..Default::default()
+        };
+        query.validate().unwrap();
+
+        let err = LogQuery::default().validate().unwrap_err();
+        assert_eq!(err.field, "contracts");
+
+        let query = LogQuery {
+            contracts: vec![HASH.to_owned()],
+            receipt_type: Some(vec![ReceiptType::Call.to_u8()]),
+            ..Default::default()
+        };
+        assert_eq!(query.validate().unwrap_err().field, "receipt_type[0]");
+    }
+
+    #[test]
+    fn query_format_names() {
+        assert!(matches!(
+            QueryFormat::from_name("yaml"),
+            Ok(QueryFormat::Yaml)
+        ));
+        assert_eq!(QueryFormat::from_name("xml").unwrap_err().field, "format");
+    }
+}
@@ -0,0 +1,235 @@
+use pyo3::prelude::*;
+
+use crate::error::validation_err;
+use crate::query::{FieldSelection, InputSelection, OutputSelection, Query, ReceiptSelection};
+
+/// Builds a query step by step.
+///
+/// Every step is validated right away, an invalid value raises a QueryValidationError
+/// and leaves the builder as it was before the step. The block range is only checked by `build`,
+/// so from_block and to_block can be set in any order.
+#[pyclass]
+#[derive(Default, Clone, Debug)]
+pub struct QueryBuilder {
+    query: Query,
+}
+
+impl QueryBuilder {
+    /// Applies a change to a copy of the query and keeps it if the result is valid.
+    fn update(&mut self, change: impl FnOnce(&mut Query)) -> PyResult<()> {
+        let mut query = self.query.clone();
+        change(&mut query);
+        query
+            .validate_selections()
+            .map_err(|e| validation_err(e.into()))?;
+        self.query = query;
+
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl QueryBuilder {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// The block to start the query from
+    fn from_block(mut slf: PyRefMut<'_, Self>, block: u64) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.from_block = block)?;
+        Ok(slf)
+    }
+
+    /// The block to end the query at, exclusive
+    fn to_block(mut slf: PyRefMut<'_, Self>, block: u64) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.to_block = Some(block))?;
+        Ok(slf)
+    }
+
+    /// Adds receipt selections, receipts matching any of the selections are returned
+    fn receipts(
+        mut slf: PyRefMut<'_, Self>,
+        selections: Vec<ReceiptSelection>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.receipts.get_or_insert_with(Vec::new).extend(selections))?;
+        Ok(slf)
+    }
+
+    /// Adds input selections, inputs matching any of the selections are returned
+    fn inputs(
+        mut slf: PyRefMut<'_, Self>,
+        selections: Vec<InputSelection>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.inputs.get_or_insert_with(Vec::new).extend(selections))?;
+        Ok(slf)
+    }
+
+    /// Adds output selections, outputs matching any of the selections are returned
+    fn outputs(
+        mut slf: PyRefMut<'_, Self>,
+        selections: Vec<OutputSelection>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.outputs.get_or_insert_with(Vec::new).extend(selections))?;
+        Ok(slf)
+    }
+
+    /// Adds block fields to the field selection
+    fn select_block_fields(
+        mut slf: PyRefMut<'_, Self>,
+        fields: Vec<String>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| add_fields(&mut q.field_selection.block, fields))?;
+        Ok(slf)
+    }
+
+    /// Adds transaction fields to the field selection
+    fn select_transaction_fields(
+        mut slf: PyRefMut<'_, Self>,
+        fields: Vec<String>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| add_fields(&mut q.field_selection.transaction, fields))?;
+        Ok(slf)
+    }
+
+    /// Adds receipt fields to the field selection
+    fn select_receipt_fields(
+        mut slf: PyRefMut<'_, Self>,
+        fields: Vec<String>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| add_fields(&mut q.field_selection.receipt, fields))?;
+        Ok(slf)
+    }
+
+    /// Adds input fields to the field selection
+    fn select_input_fields(
+        mut slf: PyRefMut<'_, Self>,
+        fields: Vec<String>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| add_fields(&mut q.field_selection.input, fields))?;
+        Ok(slf)
+    }
+
+    /// Adds output fields to the field selection
+    fn select_output_fields(
+        mut slf: PyRefMut<'_, Self>,
+        fields: Vec<String>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| add_fields(&mut q.field_selection.output, fields))?;
+        Ok(slf)
+    }
+
+    /// Selects every field of every table
+    fn all_fields(mut slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| {
+            q.field_selection = FieldSelection {
+                block: Some(schema_fields(&hyperfuel_schema::block_header())),
+                transaction: Some(schema_fields(&hyperfuel_schema::transaction())),
+                receipt: Some(schema_fields(&hyperfuel_schema::receipt())),
+                input: Some(schema_fields(&hyperfuel_schema::input())),
+                output: Some(schema_fields(&hyperfuel_schema::output())),
+            }
+        })?;
+        Ok(slf)
+    }
+
+    /// Return all blocks in the range, not only the ones related to the returned data
+    #[pyo3(signature = (include = true))]
+    fn include_all_blocks(
+        mut slf: PyRefMut<'_, Self>,
+        include: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.include_all_blocks = Some(include))?;
+        Ok(slf)
+    }
+
+    /// Maximum number of blocks that should be returned
+    fn max_num_blocks(mut slf: PyRefMut<'_, Self>, max: usize) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.max_num_blocks = Some(max))?;
+        Ok(slf)
+    }
+
+    /// Maximum number of transactions that should be returned
+    fn max_num_transactions(
+        mut slf: PyRefMut<'_, Self>,
+        max: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|q| q.max_num_transactions = Some(max))?;
+        Ok(slf)
+    }
+
+    /// The built query, raises a QueryValidationError if the block range is empty
+    fn build(&self) -> PyResult<Query> {
+        self.query
+            .validate_block_range()
+            .map_err(|e| validation_err(e.into()))?;
+        Ok(self.query.clone())
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+/// Adds fields to a selection, fields that are already selected are skipped.
+fn add_fields(selection: &mut Option<Vec<String>>, fields: Vec<String>) {
+    let selection = selection.get_or_insert_with(Vec::new);
+    for field in fields {
+        if !selection.contains(&field) {
+            selection.push(field);
+        }
+    }
+}
+
+fn schema_fields(schema: &arrow2::datatypes::Schema) -> Vec<String> {
+    schema.fields.iter().map(|f| f.name.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_step_keeps_query() {
+        pyo3::prepare_freethreaded_python();
+
+        let mut builder = QueryBuilder::new();
+        builder
+            .update(|q| add_fields(&mut q.field_selection.receipt, vec!["tx_id".to_owned()]))
+            .unwrap();
+
+        let res = builder.update(|q| {
+            q.from_block = 5;
+            add_fields(
+                &mut q.field_selection.receipt,
+                vec!["amount".to_owned(), "no_such_field".to_owned()],
+            )
+        });
+
+        assert!(res.is_err());
+        let after = builder.build().unwrap();
+        assert_eq!(after.from_block, 0);
+        assert_eq!(
+            after.field_selection.receipt,
+            Some(vec!["tx_id".to_owned()])
+        );
+    }
+
+    #[test]
+    fn block_range_in_any_order() {
+        pyo3::prepare_freethreaded_python();
+
+        let mut builder = QueryBuilder::new();
+        builder.update(|q| q.to_block = Some(10)).unwrap();
+        builder.update(|q| q.from_block = 20).unwrap();
+        assert!(builder.build().is_err());
+
+        builder.update(|q| q.to_block = Some(30)).unwrap();
+        let query = builder.build().unwrap();
+        assert_eq!((query.from_block, query.to_block), (20, Some(30)));
+    }
+}
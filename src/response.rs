@@ -1,7 +1,8 @@
-use hyperfuel_format::Hex;
 use pyo3::{pyclass, pymethods, PyObject, PyResult};
 
-use crate::types::{Block, Input, Output, Receipt, Transaction};
+use crate::types::{
+    Binary, Block, Input, Output, Receipt, ReceiptType, Transaction, TransactionStatus,
+};
 
 #[pyclass]
 #[pyo3(get_all)]
@@ -129,17 +130,42 @@ impl QueryResponseDataTyped {
     }
 }
 
-impl From<hyperfuel_client::QueryResponseTyped> for QueryResponseTyped {
-    fn from(r: hyperfuel_client::QueryResponseTyped) -> Self {
+impl QueryResponseTyped {
+    pub fn new(r: hyperfuel_client::QueryResponseTyped, native_types: bool) -> Self {
         let archive_height = r.archive_height;
         let next_block = r.next_block;
         let total_execution_time = r.total_execution_time;
         let data = QueryResponseDataTyped {
-            blocks: r.data.blocks.into_iter().map(|b| b.into()).collect(),
-            transactions: r.data.transactions.into_iter().map(|b| b.into()).collect(),
-            receipts: r.data.receipts.into_iter().map(|b| b.into()).collect(),
-            inputs: r.data.inputs.into_iter().map(|b| b.into()).collect(),
-            outputs: r.data.outputs.into_iter().map(|b| b.into()).collect(),
+            blocks: r
+                .data
+                .blocks
+                .into_iter()
+                .map(|b| Block::new(b, native_types))
+                .collect(),
+            transactions: r
+                .data
+                .transactions
+                .into_iter()
+                .map(|b| Transaction::new(b, native_types))
+                .collect(),
+            receipts: r
+                .data
+                .receipts
+                .into_iter()
+                .map(|b| Receipt::new(b, native_types))
+                .collect(),
+            inputs: r
+                .data
+                .inputs
+                .into_iter()
+                .map(|b| Input::new(b, native_types))
+                .collect(),
+            outputs: r
+                .data
+                .outputs
+                .into_iter()
+                .map(|b| Output::new(b, native_types))
+                .collect(),
         };
 
         Self {
@@ -192,11 +218,13 @@ impl LogResponse {
 #[derive(Debug, Clone)]
 pub struct LogContext {
     pub block_height: u64,
-    pub tx_id: String,
+    pub tx_id: Binary,
     pub receipt_index: u64,
-    pub receipt_type: u8,
-    pub contract_id: Option<String>,
-    pub root_contract_id: Option<String>,
+    pub receipt_type: ReceiptType,
+    /// Status of the transaction the log was emitted in, not set by preset_query_get_logs
+    pub tx_status: Option<TransactionStatus>,
+    pub contract_id: Option<Binary>,
+    pub root_contract_id: Option<Binary>,
     pub ra: Option<u64>,
     pub rb: Option<u64>,
     pub rc: Option<u64>,
@@ -205,8 +233,10 @@ pub struct LogContext {
     pub is: Option<u64>,
     pub ptr: Option<u64>,
     pub len: Option<u64>,
-    pub digest: Option<String>,
-    pub data: Option<String>,
+    pub digest: Option<Binary>,
+    pub data: Option<Binary>,
+    /// Data decoded using the ABI of the emitting contract, if one was configured
+    pub decoded: Option<PyObject>,
 }
 
 #[pymethods]
@@ -214,7 +244,7 @@ impl LogContext {
     fn __bool__(&self) -> bool {
         self.block_height == u64::default()
             || self.receipt_index == u64::default()
-            || self.receipt_type == u8::default()
+            || self.receipt_type == ReceiptType::default()
     }
 
     fn __repr__(&self) -> PyResult<String> {
@@ -226,21 +256,19 @@ impl LogContext {
     }
 }
 
-impl From<hyperfuel_client::LogResponse> for LogResponse {
-    fn from(r: hyperfuel_client::LogResponse) -> Self {
-        let archive_height = r.archive_height;
-        let next_block = r.next_block;
-        let total_execution_time = r.total_execution_time;
+impl LogResponse {
+    pub fn new(r: hyperfuel_client::LogResponse, native_types: bool) -> Self {
         let data = r
             .data
             .into_iter()
             .map(|c| LogContext {
                 block_height: c.block_height.into(),
-                tx_id: c.tx_id.encode_hex(),
+                tx_id: Binary::new(c.tx_id, native_types),
                 receipt_index: c.receipt_index.into(),
-                receipt_type: c.receipt_type.to_u8(),
-                contract_id: c.contract_id.map(|i| i.encode_hex()),
-                root_contract_id: c.root_contract_id.map(|i| i.encode_hex()),
+                receipt_type: c.receipt_type.into(),
+                tx_status: None,
+                contract_id: c.contract_id.map(|v| Binary::new(v, native_types)),
+                root_contract_id: c.root_contract_id.map(|v| Binary::new(v, native_types)),
                 ra: c.ra.map(|i| i.into()),
                 rb: c.rb.map(|i| i.into()),
                 rc: c.rc.map(|i| i.into()),
@@ -249,15 +277,57 @@ impl From<hyperfuel_client::LogResponse> for LogResponse {
                 is: c.is.map(|i| i.into()),
                 ptr: c.ptr.map(|i| i.into()),
                 len: c.len.map(|i| i.into()),
-                digest: c.digest.map(|i| i.encode_hex()),
-                data: c.data.map(|i| i.encode_hex()),
+                digest: c.digest.map(|v| Binary::new(v, native_types)),
+                data: c.data.map(|v| Binary::new(v, native_types)),
+                decoded: None,
             })
             .collect();
+
         Self {
-            archive_height,
-            next_block,
-            total_execution_time,
+            archive_height: r.archive_height,
+            next_block: r.next_block,
+            total_execution_time: r.total_execution_time,
             data,
         }
     }
+
+    /// Keeps only the receipts of the response, used for responses of log queries.
+    pub fn from_receipts(r: hyperfuel_client::QueryResponseTyped, native_types: bool) -> Self {
+        Self {
+            archive_height: r.archive_height,
+            next_block: r.next_block,
+            total_execution_time: r.total_execution_time,
+            data: r
+                .data
+                .receipts
+                .into_iter()
+                .map(|r| LogContext::new(r, native_types))
+                .collect(),
+        }
+    }
+}
+
+impl LogContext {
+    pub fn new(r: hyperfuel_format::Receipt, native_types: bool) -> Self {
+        Self {
+            block_height: r.block_height.into(),
+            tx_id: Binary::new(r.tx_id, native_types),
+            receipt_index: r.receipt_index.into(),
+            receipt_type: r.receipt_type.into(),
+            tx_status: Some(r.tx_status.into()),
+            contract_id: r.contract_id.map(|v| Binary::new(v, native_types)),
+            root_contract_id: r.root_contract_id.map(|v| Binary::new(v, native_types)),
+            ra: r.ra.map(|i| i.into()),
+            rb: r.rb.map(|i| i.into()),
+            rc: r.rc.map(|i| i.into()),
+            rd: r.rd.map(|i| i.into()),
+            pc: r.pc.map(|i| i.into()),
+            is: r.is.map(|i| i.into()),
+            ptr: r.ptr.map(|i| i.into()),
+            len: r.len.map(|i| i.into()),
+            digest: r.digest.map(|v| Binary::new(v, native_types)),
+            data: r.data.map(|v| Binary::new(v, native_types)),
+            decoded: None,
+        }
+    }
 }
@@ -0,0 +1,204 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+use crate::error::ErrorKind;
+
+const DEFAULT_MAX_NUM_RETRIES: usize = 12;
+const DEFAULT_RETRY_BASE_MS: u64 = 500;
+const DEFAULT_RETRY_CEILING_MS: u64 = 5_000;
+const DEFAULT_RETRY_ON: &[ErrorKind] = &[
+    ErrorKind::Timeout,
+    ErrorKind::Connection,
+    ErrorKind::RateLimit,
+    ErrorKind::Server,
+];
+
+/// Decides if and when a failed request is retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_num_retries: usize,
+    base: Duration,
+    ceiling: Duration,
+    retry_on: Vec<ErrorKind>,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let retry_on = match &config.retry_on {
+            Some(names) => names
+                .iter()
+                .map(|name| ErrorKind::parse(name))
+                .collect::<Result<Vec<_>>>()
+                .context("parse retry_on")?,
+            None => DEFAULT_RETRY_ON.to_vec(),
+        };
+
+        Ok(Self {
+            max_num_retries: config.max_num_retries.unwrap_or(DEFAULT_MAX_NUM_RETRIES),
+            base: Duration::from_millis(config.retry_base_ms.unwrap_or(DEFAULT_RETRY_BASE_MS)),
+            ceiling: Duration::from_millis(
+                config.retry_ceiling_ms.unwrap_or(DEFAULT_RETRY_CEILING_MS),
+            ),
+            retry_on,
+        })
+    }
+
+    /// Runs the operation, retrying it while it fails with a retryable error class and
+    /// the maximum number of retries isn't reached.
+    pub async fn run<T, F, Fut>(&self, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut num_retries = 0;
+
+        loop {
+            let err = match op().await {
+                Ok(res) => return Ok(res),
+                Err(e) => e,
+            };
+
+            if num_retries >= self.max_num_retries || !self.retry_on.contains(&ErrorKind::of(&err))
+            {
+                return Err(err);
+            }
+
+            let delay = self.backoff(num_retries);
+            log::warn!(
+                "failed to send request to hyperfuel server, retrying in {}ms: {:?}",
+                delay.as_millis(),
+                err
+            );
+            tokio::time::sleep(delay).await;
+
+            num_retries += 1;
+        }
+    }
+
+    /// Exponential backoff capped at the ceiling, with the upper half jittered
+    /// so concurrent clients don't retry in lockstep.
+    fn backoff(&self, num_retries: usize) -> Duration {
+        let factor = 2u32.saturating_pow(num_retries.try_into().unwrap_or(u32::MAX));
+        let delay = std::cmp::min(self.base.saturating_mul(factor), self.ceiling);
+
+        let half = delay / 2;
+        let jitter_ms = rand::random::<u64>() % (half.as_millis() as u64 + 1);
+
+        half + Duration::from_millis(jitter_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn policy(max_num_retries: usize, base_ms: u64, ceiling_ms: u64) -> RetryPolicy {
+        RetryPolicy {
+            max_num_retries,
+            base: Duration::from_millis(base_ms),
+            ceiling: Duration::from_millis(ceiling_ms),
+            retry_on: vec![ErrorKind::Other],
+        }
+    }
+
+    #[test]
+    fn backoff_bounds() {
+        let policy = policy(12, 500, 5_000);
+
+        for (num_retries, full_ms) in [(0, 500), (1, 1_000), (2, 2_000), (3, 4_000), (4, 5_000)] {
+            for _ in 0..100 {
+                let delay = policy.backoff(num_retries).as_millis() as u64;
+                assert!(
+                    (full_ms / 2..=full_ms).contains(&delay),
+                    "retry {}: {}ms",
+                    num_retries,
+                    delay
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = policy(12, 500, 5_000);
+
+        for num_retries in [10, 31, 32, 64, usize::MAX] {
+            let delay = policy.backoff(num_retries);
+            assert!(delay >= Duration::from_millis(2_500));
+            assert!(delay <= Duration::from_millis(5_000));
+        }
+    }
+
+    #[test]
+    fn from_config() {
+        let config = Config {
+            max_num_retries: Some(3),
+            retry_on: Some(vec!["timeout".to_owned(), "server".to_owned()]),
+            ..Default::default()
+        };
+        let policy = RetryPolicy::from_config(&config).unwrap();
+        assert_eq!(policy.max_num_retries, 3);
+        assert_eq!(policy.retry_on, vec![ErrorKind::Timeout, ErrorKind::Server]);
+        assert_eq!(policy.base, Duration::from_millis(DEFAULT_RETRY_BASE_MS));
+
+        let config = Config {
+            retry_on: Some(vec!["sometimes".to_owned()]),
+            ..Default::default()
+        };
+        assert!(RetryPolicy::from_config(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn retry_until_max() {
+        let attempts = Cell::new(0);
+        let res: Result<()> = policy(3, 1, 1)
+            .run(|| async {
+                attempts.set(attempts.get() + 1);
+                Err(anyhow!("failed"))
+            })
+            .await;
+
+        assert!(res.is_err());
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[tokio::test]
+    async fn retry_until_success() {
+        let attempts = Cell::new(0);
+        let res = policy(3, 1, 1)
+            .run(|| async {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    return Err(anyhow!("failed"));
+                }
+                Ok(attempts.get())
+            })
+            .await;
+
+        assert_eq!(res.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn skip_other_error_classes() {
+        let mut policy = policy(3, 1, 1);
+        policy.retry_on = vec![ErrorKind::Timeout];
+
+        let attempts = Cell::new(0);
+        let res: Result<()> = policy
+            .run(|| async {
+                attempts.set(attempts.get() + 1);
+                Err(anyhow!("failed"))
+            })
+            .await;
+
+        assert!(res.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -0,0 +1,537 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use hyperfuel_net_types::Query;
+use pyo3::{exceptions::PyStopAsyncIteration, prelude::*};
+use pyo3_asyncio::tokio::future_into_py;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::abi::AbiRegistry;
+use crate::blocking::block_on;
+use crate::column_mapping::Mapping;
+use crate::config::StreamConfig;
+use crate::error::py_err;
+use crate::response::{LogResponse, QueryResponseArrow, QueryResponseTyped};
+use crate::{HyperfuelClient, TableFormat};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_BATCH_SIZE: u64 = 100_000;
+const DEFAULT_BUFFER_SIZE: usize = 16;
+const DEFAULT_POLL_INTERVAL_MILLIS: u64 = 1_000;
+
+/// A single page of a paginated query response.
+pub trait Page: Send + 'static {
+    /// The block the next page should start from.
+    fn next_block(&self) -> u64;
+}
+
+impl Page for hyperfuel_client::QueryResponse {
+    fn next_block(&self) -> u64 {
+        self.next_block
+    }
+}
+
+impl Page for hyperfuel_client::QueryResponseTyped {
+    fn next_block(&self) -> u64 {
+        self.next_block
+    }
+}
+
+/// Number of pages of a block range that are buffered while an earlier range is still
+/// being received, so ranges that finish early don't pile up in memory.
+const RANGE_BUFFER_SIZE: usize = 2;
+
+/// Exclusive end of the block range of a query, capped at the head of the source.
+///
+/// The to_block of a query is exclusive, while the height of the source is the number of its
+/// latest block, so a query without a to_block fetches every block up to and including the height.
+pub fn end_block(to_block: Option<u64>, height: u64) -> u64 {
+    let head = height.saturating_add(1);
+    match to_block {
+        Some(to_block) => std::cmp::min(to_block, head),
+        None => head,
+    }
+}
+
+/// Spawns a task that runs the query until the end of the requested block range
+/// and sends the resulting pages, in block order, into the returned channel.
+///
+/// The block range is split into ranges of `batch_size` blocks and up to `concurrency`
+/// of these ranges are fetched in parallel. The task stops as soon as the receiver is dropped
+/// or closed.
+///
+/// If `tail` is set, the task doesn't stop when it reaches the height of the source. It keeps
+/// polling the height and fetches new blocks as they land until it reaches the to_block of the query.
+pub fn spawn<P, F, Fut>(
+    client: HyperfuelClient,
+    query: Query,
+    config: StreamConfig,
+    tail: bool,
+    fetch: F,
+) -> PageReceiver<P>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<P>> + Send + 'static,
+{
+    let buffer_size = config.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE).max(1);
+    let (tx, rx) = mpsc::channel(buffer_size);
+    let pages = PageReceiver::new(rx);
+    let cancel = pages.cancel.clone();
+
+    pyo3_asyncio::tokio::get_runtime().spawn(async move {
+        tokio::select! {
+            _ = tx.closed() => (),
+            _ = cancel.cancelled() => (),
+            res = run(client, query, config, tail, fetch, &tx) => {
+                if let Err(e) = res {
+                    tx.send(Err(e)).await.ok();
+                }
+            }
+        }
+    });
+
+    pages
+}
+
+async fn run<P, F, Fut>(
+    client: HyperfuelClient,
+    query: Query,
+    config: StreamConfig,
+    tail: bool,
+    fetch: F,
+    tx: &mpsc::Sender<Result<P>>,
+) -> Result<()>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<P>> + Send + 'static,
+{
+    let target = query.to_block.unwrap_or(u64::MAX);
+    let poll_interval = Duration::from_millis(
+        config
+            .poll_interval_millis
+            .unwrap_or(DEFAULT_POLL_INTERVAL_MILLIS),
+    );
+    let concurrency = config.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1);
+    let batch_size = config.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+
+    let mut from_block = query.from_block;
+
+    loop {
+        let to_block = client.fetch_end_block(query.to_block).await?;
+
+        if to_block > from_block {
+            let ranges = block_ranges(from_block, to_block, batch_size);
+            if !send_ranges(&client, &query, ranges, concurrency, &fetch, tx).await? {
+                return Ok(());
+            }
+            from_block = to_block;
+        }
+
+        if !tail || from_block >= target {
+            return Ok(());
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Fetches the block ranges and sends their pages, in block order, into the channel.
+///
+/// Up to `concurrency` ranges are paginated at the same time. Pages of the range that is being
+/// sent are forwarded as soon as they arrive, the following ranges buffer at most
+/// `RANGE_BUFFER_SIZE` pages each until it is their turn.
+///
+/// Returns false if the receiver was dropped.
+pub async fn send_ranges<P, F, Fut>(
+    client: &HyperfuelClient,
+    query: &Query,
+    ranges: Vec<(u64, u64)>,
+    concurrency: usize,
+    fetch: &F,
+    tx: &mpsc::Sender<Result<P>>,
+) -> Result<bool>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<P>> + Send + 'static,
+{
+    let mut ranges = ranges.into_iter();
+    let mut in_flight = VecDeque::with_capacity(concurrency);
+
+    // returning early drops the receivers of the ranges in flight, which stops their tasks
+    loop {
+        while in_flight.len() < concurrency {
+            match ranges.next() {
+                Some(range) => in_flight.push_back(spawn_range(client, query, range, fetch)),
+                None => break,
+            }
+        }
+
+        let mut current = match in_flight.pop_front() {
+            Some(rx) => rx,
+            None => return Ok(true),
+        };
+
+        while let Some(page) = current.recv().await {
+            if tx.send(Ok(page?)).await.is_err() {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Spawns a task that paginates a single block range into its own bounded channel.
+fn spawn_range<P, F, Fut>(
+    client: &HyperfuelClient,
+    query: &Query,
+    (from_block, to_block): (u64, u64),
+    fetch: &F,
+) -> mpsc::Receiver<Result<P>>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<P>> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(RANGE_BUFFER_SIZE);
+
+    let mut query = query.clone();
+    query.from_block = from_block;
+    query.to_block = Some(to_block);
+    let client = client.clone();
+    let fetch = fetch.clone();
+
+    tokio::spawn(async move {
+        if let Err(e) = paginate(client, query, fetch, &tx).await {
+            tx.send(Err(e)).await.ok();
+        }
+    });
+
+    rx
+}
+
+/// Keeps on making requests using the pagination mechanism (next_block) until
+/// the query reaches its to_block, sending every page into the channel as it arrives.
+async fn paginate<P, F, Fut>(
+    client: HyperfuelClient,
+    mut query: Query,
+    fetch: F,
+    tx: &mpsc::Sender<Result<P>>,
+) -> Result<()>
+where
+    P: Page,
+    F: Fn(HyperfuelClient, Query) -> Fut,
+    Fut: Future<Output = Result<P>>,
+{
+    let to_block = query.to_block.unwrap_or(u64::MAX);
+
+    loop {
+        let page = fetch(client.clone(), query.clone())
+            .await
+            .context("run query")?;
+        let next_block = check_next_block(query.from_block, page.next_block())?;
+
+        if tx.send(Ok(page)).await.is_err() || next_block >= to_block {
+            return Ok(());
+        }
+        query.from_block = next_block;
+    }
+}
+
+/// Checks that a page moved the query forward, a server that keeps on returning the same
+/// next_block would otherwise make the pagination loop forever.
+pub fn check_next_block(from_block: u64, next_block: u64) -> Result<u64> {
+    if next_block <= from_block {
+        return Err(anyhow!(
+            "pagination is stuck, the server returned next_block {} for a query starting at block {}",
+            next_block,
+            from_block
+        ));
+    }
+
+    Ok(next_block)
+}
+
+pub fn block_ranges(from_block: u64, to_block: u64, batch_size: u64) -> Vec<(u64, u64)> {
+    let mut ranges = Vec::new();
+    let mut start = from_block;
+    while start < to_block {
+        let end = std::cmp::min(start.saturating_add(batch_size), to_block);
+        ranges.push((start, end));
+        start = end;
+    }
+    ranges
+}
+
+/// Receiving end of the pages of a stream, shared by the python stream classes.
+pub struct PageReceiver<P> {
+    rx: Arc<Mutex<mpsc::Receiver<Result<P>>>>,
+    /// Stops the task that fetches the pages. It is separate from the receiver because
+    /// a pending `recv` holds the receiver lock until the next page arrives.
+    cancel: CancellationToken,
+}
+
+impl<P: Send + 'static> PageReceiver<P> {
+    fn new(rx: mpsc::Receiver<Result<P>>) -> Self {
+        Self {
+            rx: Arc::new(Mutex::new(rx)),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stops the stream, no more pages will be fetched. A pending `recv` returns the pages
+    /// that were already fetched and then None.
+    fn close(&self) -> impl Future<Output = PyResult<()>> {
+        let cancel = self.cancel.clone();
+        async move {
+            cancel.cancel();
+            Ok(())
+        }
+    }
+
+    /// Receives the next raw page, returns None when the stream is finished.
+    fn recv(&self) -> impl Future<Output = PyResult<Option<P>>> {
+        let rx = Arc::clone(&self.rx);
+        async move {
+            match rx.lock().await.recv().await {
+                Some(res) => res.map(Some).map_err(py_err),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// Implements the python iteration protocols of a stream class on top of its `next_page`.
+macro_rules! page_stream_methods {
+    ($stream:ty, $page:ty) => {
+        #[pymethods]
+        impl $stream {
+            /// Stop the stream, no more pages will be fetched.
+            pub fn close<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+                future_into_py(py, self.pages.close())
+            }
+
+            /// Receive the next page of the stream, returns None when the stream is finished.
+            pub fn recv<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+                future_into_py(py, self.next_page())
+            }
+
+            fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+                slf
+            }
+
+            fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Option<&'py PyAny>> {
+                let next_page = self.next_page();
+                let fut = future_into_py(py, async move {
+                    next_page
+                        .await?
+                        .ok_or_else(|| PyStopAsyncIteration::new_err(()))
+                })?;
+                Ok(Some(fut))
+            }
+
+            fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+                slf
+            }
+
+            fn __next__(&self, py: Python) -> PyResult<Option<$page>> {
+                block_on(py, self.next_page())
+            }
+        }
+    };
+}
+
+/// Async iterator over the pages of a query, yielding typed data.
+#[pyclass]
+pub struct QueryResponseStream {
+    pages: PageReceiver<hyperfuel_client::QueryResponseTyped>,
+    abis: Arc<AbiRegistry>,
+    native_types: bool,
+}
+
+impl QueryResponseStream {
+    pub fn new(
+        pages: PageReceiver<hyperfuel_client::QueryResponseTyped>,
+        abis: Arc<AbiRegistry>,
+        native_types: bool,
+    ) -> Self {
+        Self {
+            pages,
+            abis,
+            native_types,
+        }
+    }
+
+    /// Receive and convert the next page, returns None when the stream is finished.
+    fn next_page(&self) -> impl Future<Output = PyResult<Option<QueryResponseTyped>>> {
+        let recv = self.pages.recv();
+        let abis = Arc::clone(&self.abis);
+        let native_types = self.native_types;
+        async move {
+            let res = match recv.await? {
+                Some(res) => res,
+                None => return Ok(None),
+            };
+            let mut res =
+                crate::run_blocking(move || QueryResponseTyped::new(res, native_types)).await?;
+            abis.decode_calls(&mut res.data.receipts);
+            Ok(Some(res))
+        }
+    }
+}
+
+page_stream_methods!(QueryResponseStream, QueryResponseTyped);
+
+/// Async iterator over the pages of a query, yielding pyarrow or polars data.
+#[pyclass]
+pub struct ArrowStream {
+    pages: PageReceiver<hyperfuel_client::QueryResponse>,
+    format: TableFormat,
+    mapping: Arc<Mapping>,
+}
+
+impl ArrowStream {
+    pub fn new(
+        pages: PageReceiver<hyperfuel_client::QueryResponse>,
+        format: TableFormat,
+        mapping: Arc<Mapping>,
+    ) -> Self {
+        Self {
+            pages,
+            format,
+            mapping,
+        }
+    }
+
+    /// Receive and convert the next page, returns None when the stream is finished.
+    fn next_page(&self) -> impl Future<Output = PyResult<Option<QueryResponseArrow>>> {
+        let recv = self.pages.recv();
+        let format = self.format;
+        let mapping = Arc::clone(&self.mapping);
+        async move {
+            match recv.await? {
+                Some(res) => crate::convert_arrow_response(res, format, mapping)
+                    .await
+                    .map(Some),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+page_stream_methods!(ArrowStream, QueryResponseArrow);
+
+/// Async iterator over the pages of a log query.
+#[pyclass]
+pub struct LogStream {
+    pages: PageReceiver<hyperfuel_client::QueryResponseTyped>,
+    abis: Arc<AbiRegistry>,
+    native_types: bool,
+}
+
+impl LogStream {
+    pub fn new(
+        pages: PageReceiver<hyperfuel_client::QueryResponseTyped>,
+        abis: Arc<AbiRegistry>,
+        native_types: bool,
+    ) -> Self {
+        Self {
+            pages,
+            abis,
+            native_types,
+        }
+    }
+
+    /// Receive and convert the next page, returns None when the stream is finished.
+    fn next_page(&self) -> impl Future<Output = PyResult<Option<LogResponse>>> {
+        let recv = self.pages.recv();
+        let abis = Arc::clone(&self.abis);
+        let native_types = self.native_types;
+        async move {
+            let res = match recv.await? {
+                Some(res) => res,
+                None => return Ok(None),
+            };
+            let mut res =
+                crate::run_blocking(move || LogResponse::from_receipts(res, native_types)).await?;
+            abis.decode_logs(&mut res.data);
+            Ok(Some(res))
+        }
+    }
+}
+
+page_stream_methods!(LogStream, LogResponse);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_ranges_cover_range_in_batches() {
+        assert_eq!(block_ranges(10, 35, 10), vec![(10, 20), (20, 30), (30, 35)]);
+        assert_eq!(block_ranges(0, 10, 10), vec![(0, 10)]);
+    }
+
+    #[test]
+    fn block_ranges_of_empty_range() {
+        assert!(block_ranges(10, 10, 5).is_empty());
+        assert!(block_ranges(20, 10, 5).is_empty());
+    }
+
+    #[test]
+    fn block_ranges_near_max_block() {
+        assert_eq!(
+            block_ranges(u64::MAX - 3, u64::MAX, 10),
+            vec![(u64::MAX - 3, u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn end_block_includes_height() {
+        assert_eq!(end_block(None, 100), 101);
+        assert_eq!(end_block(Some(50), 100), 50);
+        assert_eq!(end_block(Some(500), 100), 101);
+        assert_eq!(end_block(None, u64::MAX), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn close_while_receiving() {
+        let (tx, rx) = mpsc::channel::<Result<u64>>(1);
+        let pages = PageReceiver::new(rx);
+
+        // stands in for the fetch task of `spawn`, which never sends a page here
+        let cancel = pages.cancel.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tx.closed() => (),
+                _ = cancel.cancelled() => (),
+            }
+        });
+
+        let recv = tokio::spawn(pages.recv());
+        tokio::task::yield_now().await;
+
+        tokio::time::timeout(Duration::from_secs(1), pages.close())
+            .await
+            .expect("close waited for the pending recv")
+            .unwrap();
+        let page = tokio::time::timeout(Duration::from_secs(1), recv)
+            .await
+            .expect("recv didn't end after close")
+            .unwrap()
+            .unwrap();
+        assert!(page.is_none());
+    }
+
+    #[test]
+    fn check_next_block_rejects_stuck_pagination() {
+        assert_eq!(check_next_block(10, 11).unwrap(), 11);
+        assert!(check_next_block(10, 10).is_err());
+        assert!(check_next_block(10, 5).is_err());
+    }
+}
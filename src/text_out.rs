@@ -0,0 +1,359 @@
+use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use arrow2::datatypes::Schema;
+use hyperfuel_net_types::Query;
+use pyo3::PyObject;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::config::TextOptions;
+use crate::export::{self, ExportFormat, ExportSummary, TABLES};
+use crate::types::{Block, Input, Output, Receipt, Transaction};
+use crate::HyperfuelClient;
+
+/// Format of the files written by a text export.
+#[derive(Debug, Clone, Copy)]
+pub enum TextFormat {
+    /// Comma separated values with a header row
+    Csv,
+    /// A JSON object per line
+    Ndjson,
+}
+
+pub async fn create_text_folder(
+    client: &HyperfuelClient,
+    query: Query,
+    path: String,
+    format: TextFormat,
+    options: &TextOptions,
+    progress: Option<PyObject>,
+) -> Result<ExportSummary> {
+    let fields = &query.field_selection;
+    let columns = Arc::new([
+        columns::<Block>(&hyperfuel_schema::block_header(), &fields.block)?,
+        columns::<Transaction>(&hyperfuel_schema::transaction(), &fields.transaction)?,
+        columns::<Receipt>(&hyperfuel_schema::receipt(), &fields.receipt)?,
+        columns::<Input>(&hyperfuel_schema::input(), &fields.input)?,
+        columns::<Output>(&hyperfuel_schema::output(), &fields.output)?,
+    ]);
+
+    let text = Text {
+        format,
+        columns,
+        hex_numbers: options.hex_numbers,
+    };
+
+    export::run(client, query, path, &text, &options.export, None, progress).await
+}
+
+/// Writes the tables of an export to csv or ndjson files.
+struct Text {
+    format: TextFormat,
+    /// Columns of the tables, in the order of [TABLES]
+    columns: Arc<[Vec<String>; 5]>,
+    hex_numbers: bool,
+}
+
+impl ExportFormat for Text {
+    type Page = hyperfuel_client::QueryResponseTyped;
+    type Part = Vec<TableFile>;
+
+    fn extension(&self) -> &'static str {
+        match self.format {
+            TextFormat::Csv => "csv",
+            TextFormat::Ndjson => "ndjson",
+        }
+    }
+
+    fn hex_numbers(&self) -> bool {
+        self.hex_numbers
+    }
+
+    async fn create(&self, files: Vec<PathBuf>) -> Result<Self::Part> {
+        let mut part = Vec::with_capacity(TABLES.len());
+        for ((table, columns), path) in TABLES.into_iter().zip(self.columns.iter()).zip(files) {
+            part.push(TableFile::create(path, table, self.format, columns).await?);
+        }
+
+        Ok(part)
+    }
+
+    async fn write(&self, part: &mut Self::Part, page: Self::Page) -> Result<()> {
+        let data = page.data;
+        let columns = self.columns.clone();
+        let format = self.format;
+        let hex_numbers = self.hex_numbers;
+
+        // rendering is cpu heavy so it is done on the blocking thread pool
+        let pages = tokio::task::spawn_blocking(move || {
+            let [blocks, txs, receipts, inputs, outputs] = columns.as_ref();
+            let encode = |rows: Vec<Value>, columns: &[String]| -> Result<Vec<u8>> {
+                encode_rows(rows, columns, format, hex_numbers)
+            };
+            Ok::<_, anyhow::Error>([
+                encode(to_values(data.blocks, |b| Block::new(b, true))?, blocks)?,
+                encode(
+                    to_values(data.transactions, |t| Transaction::new(t, true))?,
+                    txs,
+                )?,
+                encode(
+                    to_values(data.receipts, |r| Receipt::new(r, true))?,
+                    receipts,
+                )?,
+                encode(to_values(data.inputs, |i| Input::new(i, true))?, inputs)?,
+                encode(to_values(data.outputs, |o| Output::new(o, true))?, outputs)?,
+            ])
+        })
+        .await
+        .context("render page")??;
+
+        for (file, page) in part.iter_mut().zip(pages) {
+            file.writer
+                .write_all(&page)
+                .await
+                .with_context(|| format!("write {} rows", file.table))?;
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&self, part: Self::Part) -> Result<()> {
+        for file in part {
+            file.finish().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The output file of a table.
+struct TableFile {
+    table: &'static str,
+    writer: BufWriter<File>,
+}
+
+impl TableFile {
+    async fn create(
+        path: PathBuf,
+        table: &'static str,
+        format: TextFormat,
+        columns: &[String],
+    ) -> Result<Self> {
+        let file = File::create(&path)
+            .await
+            .with_context(|| format!("create {} output file", table))?;
+        let mut writer = BufWriter::new(file);
+
+        if let TextFormat::Csv = format {
+            let header = columns
+                .iter()
+                .map(|c| csv_cell(c))
+                .collect::<Vec<_>>()
+                .join(",");
+            writer
+                .write_all(format!("{}\n", header).as_bytes())
+                .await
+                .with_context(|| format!("write {} header", table))?;
+        }
+
+        Ok(Self { table, writer })
+    }
+
+    async fn finish(mut self) -> Result<()> {
+        self.writer
+            .shutdown()
+            .await
+            .with_context(|| format!("finish writing {} file", self.table))
+    }
+}
+
+/// Selected columns of a table that exist in its typed struct, in the order of the schema.
+fn columns<T: Serialize + Default>(
+    schema: &Schema,
+    field_selection: &BTreeSet<String>,
+) -> Result<Vec<String>> {
+    let typed = serde_json::to_value(T::default()).context("serialize typed row")?;
+    let typed = typed
+        .as_object()
+        .ok_or_else(|| anyhow!("typed row isn't serialized as an object"))?;
+
+    Ok(schema
+        .fields
+        .iter()
+        .map(|f| &f.name)
+        .filter(|name| field_selection.contains(*name) && typed.contains_key(*name))
+        .cloned()
+        .collect())
+}
+
+fn to_values<T, R: Serialize>(rows: Vec<T>, convert: impl Fn(T) -> R) -> Result<Vec<Value>> {
+    rows.into_iter()
+        .map(|row| serde_json::to_value(convert(row)).context("serialize row"))
+        .collect()
+}
+
+fn encode_rows(
+    rows: Vec<Value>,
+    columns: &[String],
+    format: TextFormat,
+    hex_numbers: bool,
+) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+
+    for row in rows {
+        let mut row = match row {
+            Value::Object(row) => row,
+            _ => return Err(anyhow!("typed row isn't serialized as an object")),
+        };
+
+        let cells = columns.iter().map(|column| {
+            let value = row.remove(column).unwrap_or(Value::Null);
+            let value = if hex_numbers && is_hex_column(column) {
+                to_hex(value)
+            } else {
+                value
+            };
+            (column, value)
+        });
+
+        match format {
+            TextFormat::Csv => {
+                let line = cells
+                    .map(|(_, value)| match value {
+                        Value::Null => String::new(),
+                        Value::String(s) => csv_cell(&s),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        value => csv_cell(&value.to_string()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                out.extend_from_slice(line.as_bytes());
+            }
+            TextFormat::Ndjson => {
+                let object = cells
+                    .map(|(column, value)| (column.clone(), value))
+                    .collect::<Map<_, _>>();
+                serde_json::to_writer(&mut out, &object).context("write json row")?;
+            }
+        }
+        out.push(b'\n');
+    }
+
+    Ok(out)
+}
+
+/// Whether `hex_numbers` applies to the column, only ids and amounts are rendered as hex.
+/// Heights, indexes and enum discriminants like receipt_type or status stay decimal.
+fn is_hex_column(column: &str) -> bool {
+    column == "id"
+        || column.ends_with("_id")
+        || column.ends_with("_ids")
+        || column == "amount"
+        || column.ends_with("_amount")
+}
+
+/// Renders integers as 0x prefixed hex.
+fn to_hex(value: Value) -> Value {
+    match value {
+        Value::Number(n) => match n.as_u64() {
+            Some(n) => Value::String(format!("0x{:x}", n)),
+            None => Value::Number(n),
+        },
+        Value::Array(values) => Value::Array(values.into_iter().map(to_hex).collect()),
+        value => value,
+    }
+}
+
+/// Quotes a CSV cell if it contains a separator, a quote or a line break.
+fn csv_cell(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn quote_csv_cells() {
+        assert_eq!(csv_cell("0x01"), "0x01");
+        assert_eq!(csv_cell(""), "");
+        assert_eq!(csv_cell("a,b"), "\"a,b\"");
+        assert_eq!(csv_cell("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_cell("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_cell("a\rb"), "\"a\rb\"");
+    }
+
+    #[test]
+    fn render_hex() {
+        assert_eq!(to_hex(json!(0)), json!("0x0"));
+        assert_eq!(to_hex(json!(255)), json!("0xff"));
+        assert_eq!(to_hex(json!(u64::MAX)), json!("0xffffffffffffffff"));
+        assert_eq!(to_hex(json!([1, 16])), json!(["0x1", "0x10"]));
+        assert_eq!(to_hex(json!(-1)), json!(-1));
+        assert_eq!(to_hex(json!("0x01")), json!("0x01"));
+        assert_eq!(to_hex(Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn hex_columns() {
+        for column in ["amount", "mint_amount", "id", "tx_id", "input_asset_ids"] {
+            assert!(is_hex_column(column), "{}", column);
+        }
+        for column in [
+            "block_height",
+            "tx_index",
+            "receipt_type",
+            "tx_type",
+            "status",
+            "height",
+        ] {
+            assert!(!is_hex_column(column), "{}", column);
+        }
+    }
+
+    #[test]
+    fn encode_csv_rows() {
+        let columns = ["block_height", "receipt_type", "amount", "data"].map(String::from);
+        let rows = vec![
+            json!({"block_height": 16, "receipt_type": 6, "amount": 255, "data": "0x01"}),
+            json!({"block_height": 17, "receipt_type": 0, "amount": null, "data": "a,b"}),
+        ];
+
+        let out = encode_rows(rows.clone(), &columns, TextFormat::Csv, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "16,6,255,0x01\n17,0,,\"a,b\"\n"
+        );
+
+        let out = encode_rows(rows, &columns, TextFormat::Csv, true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "16,6,0xff,0x01\n17,0,,\"a,b\"\n"
+        );
+    }
+
+    #[test]
+    fn encode_ndjson_rows() {
+        let columns = ["tx_status", "amount", "missing"].map(String::from);
+        let rows = vec![json!({"tx_status": 1, "amount": 16, "other": true})];
+
+        let out = encode_rows(rows, &columns, TextFormat::Ndjson, true).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&out).unwrap(),
+            json!({"tx_status": 1, "amount": "0x10", "missing": null})
+        );
+        assert_eq!(out.last(), Some(&b'\n'));
+    }
+}
@@ -1,40 +1,444 @@
+use std::fmt;
+
 use hyperfuel_format::Hex;
-use pyo3::{pyclass, pymethods, PyResult};
+use pyo3::{
+    pyclass, pymethods,
+    types::{PyBytes, PyLong},
+    IntoPy, PyAny, PyObject, PyResult, Python,
+};
+use serde::{Serialize, Serializer};
+
+/// Type of a receipt.
+///
+/// Can be passed anywhere the numeric value is accepted, e.g. in the selections of a query.
+#[pyclass(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptType {
+    #[default]
+    Call = 0,
+    Return = 1,
+    ReturnData = 2,
+    Panic = 3,
+    Revert = 4,
+    Log = 5,
+    LogData = 6,
+    Transfer = 7,
+    TransferOut = 8,
+    ScriptResult = 9,
+    MessageOut = 10,
+    Mint = 11,
+    Burn = 12,
+}
+
+#[pymethods]
+impl ReceiptType {
+    fn __index__(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
+}
+
+impl Serialize for ReceiptType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl From<hyperfuel_format::ReceiptType> for ReceiptType {
+    fn from(t: hyperfuel_format::ReceiptType) -> Self {
+        match t {
+            hyperfuel_format::ReceiptType::Call => Self::Call,
+            hyperfuel_format::ReceiptType::Return => Self::Return,
+            hyperfuel_format::ReceiptType::ReturnData => Self::ReturnData,
+            hyperfuel_format::ReceiptType::Panic => Self::Panic,
+            hyperfuel_format::ReceiptType::Revert => Self::Revert,
+            hyperfuel_format::ReceiptType::Log => Self::Log,
+            hyperfuel_format::ReceiptType::LogData => Self::LogData,
+            hyperfuel_format::ReceiptType::Transfer => Self::Transfer,
+            hyperfuel_format::ReceiptType::TransferOut => Self::TransferOut,
+            hyperfuel_format::ReceiptType::ScriptResult => Self::ScriptResult,
+            hyperfuel_format::ReceiptType::MessageOut => Self::MessageOut,
+            hyperfuel_format::ReceiptType::Mint => Self::Mint,
+            hyperfuel_format::ReceiptType::Burn => Self::Burn,
+        }
+    }
+}
+
+/// Type of an input.
+///
+/// Can be passed anywhere the numeric value is accepted, e.g. in the selections of a query.
+#[pyclass(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    #[default]
+    Coin = 0,
+    Contract = 1,
+    Message = 2,
+}
+
+#[pymethods]
+impl InputType {
+    fn __index__(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
+}
+
+impl Serialize for InputType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl From<hyperfuel_format::InputType> for InputType {
+    fn from(t: hyperfuel_format::InputType) -> Self {
+        match t {
+            hyperfuel_format::InputType::InputCoin => Self::Coin,
+            hyperfuel_format::InputType::InputContract => Self::Contract,
+            hyperfuel_format::InputType::InputMessage => Self::Message,
+        }
+    }
+}
+
+/// Type of an output.
+///
+/// Can be passed anywhere the numeric value is accepted, e.g. in the selections of a query.
+#[pyclass(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputType {
+    #[default]
+    CoinOutput = 0,
+    ContractOutput = 1,
+    ChangeOutput = 2,
+    VariableOutput = 3,
+    ContractCreated = 4,
+}
+
+#[pymethods]
+impl OutputType {
+    fn __index__(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
+}
+
+impl Serialize for OutputType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl From<hyperfuel_format::OutputType> for OutputType {
+    fn from(t: hyperfuel_format::OutputType) -> Self {
+        match t {
+            hyperfuel_format::OutputType::CoinOutput => Self::CoinOutput,
+            hyperfuel_format::OutputType::ContractOutput => Self::ContractOutput,
+            hyperfuel_format::OutputType::ChangeOutput => Self::ChangeOutput,
+            hyperfuel_format::OutputType::VariableOutput => Self::VariableOutput,
+            hyperfuel_format::OutputType::ContractCreated => Self::ContractCreated,
+        }
+    }
+}
+
+/// Type of a transaction.
+///
+/// Can be passed anywhere the numeric value is accepted, e.g. in the selections of a query.
+#[pyclass(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionType {
+    #[default]
+    Script = 0,
+    Create = 1,
+    Mint = 2,
+    Upgrade = 3,
+    Upload = 4,
+}
+
+#[pymethods]
+impl TransactionType {
+    fn __index__(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
+}
+
+impl Serialize for TransactionType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl From<hyperfuel_format::TransactionType> for TransactionType {
+    fn from(t: hyperfuel_format::TransactionType) -> Self {
+        match t {
+            hyperfuel_format::TransactionType::Script => Self::Script,
+            hyperfuel_format::TransactionType::Create => Self::Create,
+            hyperfuel_format::TransactionType::Mint => Self::Mint,
+            hyperfuel_format::TransactionType::Upgrade => Self::Upgrade,
+            hyperfuel_format::TransactionType::Upload => Self::Upload,
+        }
+    }
+}
+
+/// Status of a transaction.
+///
+/// Can be passed anywhere the numeric value is accepted, e.g. in the selections of a query.
+#[pyclass(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+    #[default]
+    Submitted = 0,
+    Success = 1,
+    SqueezedOut = 2,
+    Failure = 3,
+}
+
+#[pymethods]
+impl TransactionStatus {
+    fn __index__(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
+}
+
+impl Serialize for TransactionStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl From<hyperfuel_format::TransactionStatus> for TransactionStatus {
+    fn from(t: hyperfuel_format::TransactionStatus) -> Self {
+        match t {
+            hyperfuel_format::TransactionStatus::Submitted => Self::Submitted,
+            hyperfuel_format::TransactionStatus::Success => Self::Success,
+            hyperfuel_format::TransactionStatus::SqueezedOut => Self::SqueezedOut,
+            hyperfuel_format::TransactionStatus::Failure => Self::Failure,
+        }
+    }
+}
+
+/// A binary field, hex encoded unless the client is configured to use native types
+/// in which case it is returned as bytes.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Binary {
+    Hex(String),
+    Bytes(Vec<u8>),
+}
+
+impl Binary {
+    pub fn new<T: Hex + AsRef<[u8]>>(value: T, native_types: bool) -> Self {
+        if native_types {
+            Self::Bytes(value.as_ref().to_vec())
+        } else {
+            Self::Hex(value.encode_hex())
+        }
+    }
+
+    /// Hex encoded value, prefixed with 0x.
+    pub fn to_hex(&self) -> String {
+        match self {
+            Self::Hex(hex) => hex.clone(),
+            Self::Bytes(bytes) => hyperfuel_format::Data::from(bytes.clone()).encode_hex(),
+        }
+    }
+}
+
+impl Default for Binary {
+    fn default() -> Self {
+        Self::Hex(String::new())
+    }
+}
+
+impl fmt::Debug for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_hex())
+    }
+}
+
+impl fmt::Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Serialize for Binary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl IntoPy<PyObject> for Binary {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Self::Hex(hex) => hex.into_py(py),
+            Self::Bytes(bytes) => PyBytes::new(py, &bytes).into_py(py),
+        }
+    }
+}
+
+/// A big endian quantity, hex encoded unless the client is configured to use native types
+/// in which case it is returned as an int.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Number {
+    Hex(String),
+    Int(Vec<u8>),
+}
+
+impl Number {
+    pub fn new(value: hyperfuel_format::Quantity, native_types: bool) -> Self {
+        if native_types {
+            Self::Int(value.as_ref().to_vec())
+        } else {
+            Self::Hex(value.encode_hex())
+        }
+    }
+}
+
+impl Default for Number {
+    fn default() -> Self {
+        Self::Hex(String::new())
+    }
+}
+
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex(hex) => write!(f, "{:?}", hex),
+            Self::Int(bytes) if bytes.len() <= 16 => write!(f, "{}", be_u128(bytes)),
+            Self::Int(bytes) => write!(
+                f,
+                "{:?}",
+                hyperfuel_format::Data::from(bytes.clone()).encode_hex()
+            ),
+        }
+    }
+}
+
+/// Serialized as an integer if it fits into 64 bits, as a hex string otherwise.
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Hex(hex) => serializer.serialize_str(hex),
+            Self::Int(bytes) => match u64::try_from(be_u128(bytes)) {
+                Ok(value) if bytes.len() <= 16 => serializer.serialize_u64(value),
+                _ => serializer
+                    .serialize_str(&hyperfuel_format::Data::from(bytes.clone()).encode_hex()),
+            },
+        }
+    }
+}
+
+impl Number {
+    /// Converts the value into a python str or int, ints wider than 128 bits are built by
+    /// `int.from_bytes` which can fail.
+    pub fn to_object(&self, py: Python<'_>) -> PyResult<PyObject> {
+        match self {
+            Self::Hex(hex) => Ok(hex.into_py(py)),
+            Self::Int(bytes) if bytes.len() <= 16 => Ok(be_u128(bytes).into_py(py)),
+            Self::Int(bytes) => Ok(py
+                .get_type::<PyLong>()
+                .call_method1("from_bytes", (PyBytes::new(py, bytes), "big"))?
+                .into_py(py)),
+        }
+    }
+}
+
+fn be_u128(bytes: &[u8]) -> u128 {
+    bytes
+        .iter()
+        .fold(0u128, |acc, b| (acc << 8) | u128::from(*b))
+}
 
 /// The block header contains metadata about a certain block.
 #[pyclass]
-#[pyo3(get_all)]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct Block {
     /// String of the header
-    pub id: String,
+    #[pyo3(get)]
+    pub id: Binary,
     /// The block height for the data availability layer up to which (inclusive) input messages are processed.
+    #[pyo3(get)]
     pub da_height: u64,
     /// version of consensus
+    #[pyo3(get)]
     pub consensus_parameters_version: u64,
     /// version of the state transition
+    #[pyo3(get)]
     pub state_transition_bytecode_version: u64,
     /// The number of transactions in the block.
-    pub transactions_count: String,
+    pub transactions_count: Number,
     /// The number of receipt messages in the block.
-    pub message_receipt_count: String,
+    pub message_receipt_count: Number,
     /// The merkle root of the transactions in the block.
-    pub transactions_root: String,
+    #[pyo3(get)]
+    pub transactions_root: Binary,
     /// The merkle root of the receipt messages in the block.
-    pub message_outbox_root: String,
-    pub event_inbox_root: String,
+    #[pyo3(get)]
+    pub message_outbox_root: Binary,
+    #[pyo3(get)]
+    pub event_inbox_root: Binary,
     /// The block height.
+    #[pyo3(get)]
     pub height: u64,
     /// The merkle root of all previous consensus header Stringes (not including this block).
-    pub prev_root: String,
+    #[pyo3(get)]
+    pub prev_root: Binary,
     /// The timestamp for the block.
+    #[pyo3(get)]
     pub time: u64,
     /// The String of the serialized application header for this block.
-    pub application_hash: String,
+    #[pyo3(get)]
+    pub application_hash: Binary,
 }
 
 #[pymethods]
 impl Block {
+    #[getter]
+    fn transactions_count(&self, py: Python) -> PyResult<PyObject> {
+        self.transactions_count.to_object(py)
+    }
+
+    #[getter]
+    fn message_receipt_count(&self, py: Python) -> PyResult<PyObject> {
+        self.message_receipt_count.to_object(py)
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("{:?}", self))
     }
@@ -47,30 +451,30 @@ impl Block {
 /// An object containing information about a transaction.
 #[pyclass]
 #[pyo3(get_all)]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct Transaction {
     /// block the transaction is in.
     pub block_height: u64,
     /// A unique transaction id.
-    pub id: String,
+    pub id: Binary,
     /// An array of asset ids used for the transaction inputs.
-    pub input_asset_ids: Option<Vec<String>>,
+    pub input_asset_ids: Option<Vec<Binary>>,
     // Contract object -> bincode into schema
     /// An array of contracts used for the transaction inputs.
-    pub input_contracts: Option<Vec<String>>,
+    pub input_contracts: Option<Vec<Binary>>,
     /// A contract used for the transaction input.
     /// A unique 32 byte identifier for the UTXO for a contract used for the transaction input.
-    pub input_contract_utxo_id: Option<String>,
+    pub input_contract_utxo_id: Option<Binary>,
     /// The root of amount of coins owned by contract before transaction execution for a contract used for the transaction input.
-    pub input_contract_balance_root: Option<String>,
+    pub input_contract_balance_root: Option<Binary>,
     /// The state root of contract before transaction execution for a contract used for the transaction input.
-    pub input_contract_state_root: Option<String>,
+    pub input_contract_state_root: Option<Binary>,
     /// A pointer to the TX whose output is being spent for a contract used for the transaction input.
     pub input_contract_tx_pointer_block_height: Option<u64>,
     /// A pointer to the TX whose output is being spent for a contract used for the transaction input.
     pub input_contract_tx_pointer_tx_index: Option<u64>,
     /// The contract id for a contract used for the transaction input.
-    pub input_contract: Option<String>,
+    pub input_contract: Option<Binary>,
     pub policies_tip: Option<u64>,
     pub policies_witness_limit: Option<u64>,
     pub policies_maturity: Option<u64>,
@@ -82,25 +486,25 @@ pub struct Transaction {
     /// The amount minted in the transaction.
     pub mint_amount: Option<u64>,
     /// The asset ID for coins minted in the transaction.
-    pub mint_asset_id: Option<String>,
+    pub mint_asset_id: Option<Binary>,
     pub mint_gas_price: Option<u64>,
     /// The location of the transaction in the block.
     pub tx_pointer_block_height: Option<u64>,
     pub tx_pointer_tx_index: Option<u64>,
     /// Script, creating a new contract, or minting new coins
-    pub tx_type: u8,
+    pub tx_type: TransactionType,
     /// The index of the input from a transaction that changed the state of a contract.
     pub output_contract_input_index: Option<u64>,
     /// The root of amount of coins owned by contract after transaction execution from a transaction that changed the state of a contract.
-    pub output_contract_balance_root: Option<String>,
+    pub output_contract_balance_root: Option<Binary>,
     /// The state root of contract after transaction execution from a transaction that changed the state of a contract.
-    pub output_contract_state_root: Option<String>,
+    pub output_contract_state_root: Option<Binary>,
     /// An array of witnesses.
-    pub witnesses: Option<String>,
+    pub witnesses: Option<Binary>,
     /// The root of the receipts.
-    pub receipts_root: Option<String>,
+    pub receipts_root: Option<Binary>,
     /// The status type of the transaction.
-    pub status: u8,
+    pub status: TransactionStatus,
     /// for SubmittedStatus, SuccessStatus, and FailureStatus, the time a transaction was submitted, successful, or failed
     pub time: u64,
     /// for SuccessStatus, the state of the program execution
@@ -108,20 +512,20 @@ pub struct Transaction {
     /// for SqueezedOutStatus & FailureStatus, the reason the transaction was squeezed out or failed
     pub reason: Option<String>,
     /// The script to execute.
-    pub script: Option<String>,
+    pub script: Option<Binary>,
     /// The script input parameters.
-    pub script_data: Option<String>,
+    pub script_data: Option<Binary>,
     /// The witness index of contract bytecode.
     pub bytecode_witness_index: Option<u64>,
-    pub bytecode_root: Option<String>,
+    pub bytecode_root: Option<Binary>,
     pub subsection_index: Option<u64>,
     pub subsections_number: Option<u64>,
-    pub proof_set: Option<String>,
+    pub proof_set: Option<Binary>,
     pub consensus_parameters_upgrade_purpose_witness_index: Option<u64>,
-    pub consensus_parameters_upgrade_purpose_checksum: Option<String>,
-    pub state_transition_upgrade_purpose_root: Option<String>,
+    pub consensus_parameters_upgrade_purpose_checksum: Option<Binary>,
+    pub state_transition_upgrade_purpose_root: Option<Binary>,
     /// The salt value for the transaction.
-    pub salt: Option<String>,
+    pub salt: Option<Binary>,
 }
 
 #[pymethods]
@@ -138,18 +542,18 @@ impl Transaction {
 /// An object representing all possible types of receipts.
 #[pyclass]
 #[pyo3(get_all)]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Receipt {
     /// Index of the receipt in the block
     pub receipt_index: u64,
     /// Contract that produced the receipt
-    pub root_contract_id: Option<String>,
+    pub root_contract_id: Option<Binary>,
     /// transaction that this receipt originated from
-    pub tx_id: String,
+    pub tx_id: Binary,
     /// The status type of the transaction this receipt originated from
-    pub tx_status: u8,
+    pub tx_status: TransactionStatus,
     /// The type of the transaction this receipt originated from
-    pub tx_type: u8,
+    pub tx_type: TransactionType,
     /// block that the receipt originated in
     pub block_height: u64,
     /// The value of the program counter register $pc, which is the memory address of the current instruction.
@@ -157,13 +561,13 @@ pub struct Receipt {
     /// The value of register $is, which is the pointer to the start of the currently-executing code.
     pub is: Option<u64>,
     /// The recipient contract
-    pub to: Option<String>,
+    pub to: Option<Binary>,
     /// The recipient address
-    pub to_address: Option<String>,
+    pub to_address: Option<Binary>,
     /// The amount of coins transferred.
     pub amount: Option<u64>,
     /// The asset id of the coins transferred.
-    pub asset_id: Option<String>,
+    pub asset_id: Option<Binary>,
     /// The gas used for the transaction.
     pub gas: Option<u64>,
     /// The first parameter for a CALL receipt type, holds the function selector.
@@ -175,7 +579,7 @@ pub struct Receipt {
     /// The value of the pointer register, used for debugging.
     pub ptr: Option<u64>,
     /// A 32-byte String of MEM[$rC, $rD]. The syntax MEM[x, y] means the memory range starting at byte x, of length y bytes.
-    pub digest: Option<String>,
+    pub digest: Option<Binary>,
     /// The decimal string representation of an 8-bit unsigned integer for the panic reason. Only returned if the receipt type is PANIC.
     pub reason: Option<u64>,
     /// The value of register $rA.
@@ -189,23 +593,26 @@ pub struct Receipt {
     /// The length of the receipt.
     pub len: Option<u64>,
     /// The type of receipt.
-    pub receipt_type: u8,
+    pub receipt_type: ReceiptType,
     /// 0 if script exited successfully, any otherwise.
     pub result: Option<u64>,
     /// The amount of gas consumed by the script.
     pub gas_used: Option<u64>,
     /// The receipt data.
-    pub data: Option<String>,
+    pub data: Option<Binary>,
     /// The address of the message sender.
-    pub sender: Option<String>,
+    pub sender: Option<Binary>,
     /// The address of the message recipient.
-    pub recipient: Option<String>,
+    pub recipient: Option<Binary>,
     /// The nonce value for a message.
-    pub nonce: Option<String>,
+    pub nonce: Option<Binary>,
     /// Current context if in an internal context. null otherwise
-    pub contract_id: Option<String>,
+    pub contract_id: Option<Binary>,
     /// The sub id.
-    pub sub_id: Option<String>,
+    pub sub_id: Option<Binary>,
+    /// The called function, only set on CALL receipts to a contract that has an ABI configured
+    #[serde(skip)]
+    pub decoded_call: Option<DecodedCall>,
 }
 
 #[pymethods]
@@ -219,30 +626,56 @@ impl Receipt {
     }
 }
 
+/// A contract call resolved using the ABI of the called contract.
+#[pyclass]
+#[pyo3(get_all)]
+#[derive(Debug, Clone)]
+pub struct DecodedCall {
+    /// Name of the called function
+    pub function: String,
+    /// The function selector, param1 of the CALL receipt
+    pub selector: u64,
+    /// Arguments of the call keyed by name. Only set if they can be read from the receipt, which is
+    /// the case when the function takes no arguments or a single one passed in a register
+    /// (bool, unit or an integer up to u64).
+    pub args: Option<PyObject>,
+}
+
+#[pymethods]
+impl DecodedCall {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+
+    fn __str__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
 /// An object representing all possible types of inputs.  InputCoin, InputContract, InputMessage
 #[pyclass]
 #[pyo3(get_all)]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct Input {
     /// transaction that this input originated from
-    pub tx_id: String,
+    pub tx_id: Binary,
     /// The status type of the transaction this input originated from
-    pub tx_status: u8,
+    pub tx_status: TransactionStatus,
     /// The type of the transaction this input originated from
-    pub tx_type: u8,
+    pub tx_type: TransactionType,
     /// block that the input originated in
     pub block_height: u64,
     /// InputCoin, InputContract, or InputMessage
-    pub input_type: u8,
+    pub input_type: InputType,
     /// A unique 32 byte identifier for the UTXO.
-    pub utxo_id: Option<String>,
+    pub utxo_id: Option<Binary>,
     /// The owning address or predicate root.
-    pub owner: Option<String>,
+    pub owner: Option<Binary>,
     /// for InputCoin type: The amount of coins.
     /// for InputMessage type: The amount sent in the message.
     pub amount: Option<u64>,
     /// The asset ID of the coins.
-    pub asset_id: Option<String>,
+    pub asset_id: Option<Binary>,
     /// A pointer to the transaction whose output is being spent.
     pub tx_pointer_block_height: Option<u64>,
     pub tx_pointer_tx_index: Option<u64>,
@@ -251,23 +684,23 @@ pub struct Input {
     /// The amount of gas used in the predicate transaction.
     pub predicate_gas_used: Option<u64>,
     /// The predicate bytecode.
-    pub predicate: Option<String>,
+    pub predicate: Option<Binary>,
     /// The predicate input parameters.
-    pub predicate_data: Option<String>,
+    pub predicate_data: Option<Binary>,
     /// The root of amount of coins owned by contract before transaction execution.
-    pub balance_root: Option<String>,
+    pub balance_root: Option<Binary>,
     /// The state root of contract before transaction execution.
-    pub state_root: Option<String>,
+    pub state_root: Option<Binary>,
     /// The input contract.
-    pub contract: Option<String>,
+    pub contract: Option<Binary>,
     /// The sender address of the message.
-    pub sender: Option<String>,
+    pub sender: Option<Binary>,
     /// The recipient address of the message.
-    pub recipient: Option<String>,
+    pub recipient: Option<Binary>,
     /// A nonce value for the message input, which is determined by the sending system and is published at the time the message is sent.
-    pub nonce: Option<String>,
+    pub nonce: Option<Binary>,
     /// The message data.
-    pub data: Option<String>,
+    pub data: Option<Binary>,
 }
 
 #[pymethods]
@@ -284,33 +717,33 @@ impl Input {
 /// An object representing all possible types of Outputs. CoinOutput, ContractOutput, ChangeOutput, VariableOutput, ContractCreated
 #[pyclass]
 #[pyo3(get_all)]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct Output {
     /// transaction that this out originated from
-    pub tx_id: String,
+    pub tx_id: Binary,
     /// The status type of the transaction this receipt originated from
-    pub tx_status: u8,
+    pub tx_status: TransactionStatus,
     /// The type of the transaction this output originated from
-    pub tx_type: u8,
+    pub tx_type: TransactionType,
     /// block that the output originated in
     pub block_height: u64,
     /// CoinOutput, ContractOutput, ChangeOutput, VariableOutput, or ContractCreated
-    pub output_type: u8,
+    pub output_type: OutputType,
     /// The address the coins were sent to.
-    pub to: Option<String>,
+    pub to: Option<Binary>,
     /// The amount of coins in the output.
     pub amount: Option<u64>,
     /// The asset id for the coins sent.
-    pub asset_id: Option<String>,
+    pub asset_id: Option<Binary>,
     /// The index of the input.
     pub input_index: Option<u64>,
     /// The root of amount of coins owned by contract after transaction execution.
-    pub balance_root: Option<String>,
+    pub balance_root: Option<Binary>,
     /// for ContractedCreated type: The initial state root of contract.
     /// for ContractOutput type: The state root of contract after transaction execution.
-    pub state_root: Option<String>,
+    pub state_root: Option<Binary>,
     /// for ContractCreated type: The contract that was created.
-    pub contract: Option<String>,
+    pub contract: Option<Binary>,
 }
 
 #[pymethods]
@@ -324,172 +757,187 @@ impl Output {
     }
 }
 
-impl From<hyperfuel_format::BlockHeader> for Block {
-    fn from(b: hyperfuel_format::BlockHeader) -> Self {
+impl Block {
+    pub fn new(b: hyperfuel_format::BlockHeader, native_types: bool) -> Self {
         Self {
-            id: b.id.encode_hex(),
+            id: Binary::new(b.id, native_types),
             da_height: b.da_height.into(),
-            transactions_count: b.transactions_count.encode_hex(),
-            message_receipt_count: b.message_receipt_count.encode_hex(),
-            transactions_root: b.transactions_root.encode_hex(),
+            transactions_count: Number::new(b.transactions_count, native_types),
+            message_receipt_count: Number::new(b.message_receipt_count, native_types),
+            transactions_root: Binary::new(b.transactions_root, native_types),
             height: b.height.into(),
-            prev_root: b.prev_root.encode_hex(),
+            prev_root: Binary::new(b.prev_root, native_types),
             time: b.time.into(),
-            application_hash: b.application_hash.encode_hex(),
+            application_hash: Binary::new(b.application_hash, native_types),
             consensus_parameters_version: b.consensus_parameters_version.into(),
             state_transition_bytecode_version: b.state_transition_bytecode_version.into(),
-            message_outbox_root: b.message_outbox_root.encode_hex(),
-            event_inbox_root: b.event_inbox_root.encode_hex(),
+            message_outbox_root: Binary::new(b.message_outbox_root, native_types),
+            event_inbox_root: Binary::new(b.event_inbox_root, native_types),
         }
     }
 }
 
-impl From<hyperfuel_format::Transaction> for Transaction {
-    fn from(t: hyperfuel_format::Transaction) -> Self {
+impl Transaction {
+    pub fn new(t: hyperfuel_format::Transaction, native_types: bool) -> Self {
         Self {
             block_height: t.block_height.into(),
-            id: t.id.encode_hex(),
-            input_asset_ids: t
-                .input_asset_ids
-                .map(|d| d.into_iter().map(|i| i.encode_hex()).collect()),
-            input_contracts: t
-                .input_contracts
-                .map(|d| d.into_iter().map(|i| i.encode_hex()).collect()),
-            input_contract_utxo_id: t.input_contract_utxo_id.map(|d| d.encode_hex()),
-            input_contract_balance_root: t.input_contract_balance_root.map(|d| d.encode_hex()),
-            input_contract_state_root: t.input_contract_state_root.map(|d| d.encode_hex()),
+            id: Binary::new(t.id, native_types),
+            input_asset_ids: t.input_asset_ids.map(|d| {
+                d.into_iter()
+                    .map(|v| Binary::new(v, native_types))
+                    .collect()
+            }),
+            input_contracts: t.input_contracts.map(|d| {
+                d.into_iter()
+                    .map(|v| Binary::new(v, native_types))
+                    .collect()
+            }),
+            input_contract_utxo_id: t
+                .input_contract_utxo_id
+                .map(|v| Binary::new(v, native_types)),
+            input_contract_balance_root: t
+                .input_contract_balance_root
+                .map(|v| Binary::new(v, native_types)),
+            input_contract_state_root: t
+                .input_contract_state_root
+                .map(|v| Binary::new(v, native_types)),
             input_contract_tx_pointer_block_height: t
                 .input_contract_tx_pointer_block_height
                 .map(|t| t.into()),
             input_contract_tx_pointer_tx_index: t
                 .input_contract_tx_pointer_tx_index
                 .map(|t| t.into()),
-            input_contract: t.input_contract.map(|d| d.encode_hex()),
+            input_contract: t.input_contract.map(|v| Binary::new(v, native_types)),
             maturity: t.maturity.map(|t| t.into()),
             mint_amount: t.mint_amount.map(|t| t.into()),
-            mint_asset_id: t.mint_asset_id.map(|d| d.encode_hex()),
+            mint_asset_id: t.mint_asset_id.map(|v| Binary::new(v, native_types)),
             tx_pointer_block_height: t.tx_pointer_block_height.map(|t| t.into()),
             tx_pointer_tx_index: t.tx_pointer_tx_index.map(|t| t.into()),
-            tx_type: t.tx_type.to_u8(),
+            tx_type: t.tx_type.into(),
             output_contract_input_index: t.output_contract_input_index.map(|t| t.into()),
-            output_contract_balance_root: t.output_contract_balance_root.map(|d| d.encode_hex()),
-            output_contract_state_root: t.output_contract_state_root.map(|d| d.encode_hex()),
-            witnesses: t.witnesses.map(|d| d.encode_hex()),
-            receipts_root: t.receipts_root.map(|d| d.encode_hex()),
-            status: t.status.as_u8(),
+            output_contract_balance_root: t
+                .output_contract_balance_root
+                .map(|v| Binary::new(v, native_types)),
+            output_contract_state_root: t
+                .output_contract_state_root
+                .map(|v| Binary::new(v, native_types)),
+            witnesses: t.witnesses.map(|v| Binary::new(v, native_types)),
+            receipts_root: t.receipts_root.map(|v| Binary::new(v, native_types)),
+            status: t.status.into(),
             time: t.time.into(),
             reason: t.reason,
-            script: t.script.map(|d| d.encode_hex()),
-            script_data: t.script_data.map(|d| d.encode_hex()),
+            script: t.script.map(|v| Binary::new(v, native_types)),
+            script_data: t.script_data.map(|v| Binary::new(v, native_types)),
             bytecode_witness_index: t.bytecode_witness_index.map(|t| t.into()),
-            salt: t.salt.map(|d| d.encode_hex()),
+            salt: t.salt.map(|v| Binary::new(v, native_types)),
             policies_tip: t.policies_tip.map(|t| t.into()),
             policies_witness_limit: t.policies_witness_limit.map(|t| t.into()),
             policies_maturity: t.policies_maturity.map(|t| t.into()),
             policies_max_fee: t.policies_max_fee.map(|t| t.into()),
             script_gas_limit: t.script_gas_limit.map(|t| t.into()),
             mint_gas_price: t.mint_amount.map(|t| t.into()),
-            bytecode_root: t.bytecode_root.map(|r| r.encode_hex()),
+            bytecode_root: t.bytecode_root.map(|v| Binary::new(v, native_types)),
             subsection_index: t.subsection_index.map(|t| t.into()),
             subsections_number: t.subsections_number.map(|t| t.into()),
-            proof_set: t.proof_set.map(|p| p.encode_hex()),
+            proof_set: t.proof_set.map(|v| Binary::new(v, native_types)),
             consensus_parameters_upgrade_purpose_witness_index: t
                 .consensus_parameters_upgrade_purpose_witness_index
                 .map(|t| t.into()),
             consensus_parameters_upgrade_purpose_checksum: t
                 .consensus_parameters_upgrade_purpose_checksum
-                .map(|a| a.encode_hex()),
+                .map(|v| Binary::new(v, native_types)),
             state_transition_upgrade_purpose_root: t
                 .state_transition_upgrade_purpose_root
-                .map(|a| a.encode_hex()),
+                .map(|v| Binary::new(v, native_types)),
         }
     }
 }
 
-impl From<hyperfuel_format::Receipt> for Receipt {
-    fn from(r: hyperfuel_format::Receipt) -> Self {
+impl Receipt {
+    pub fn new(r: hyperfuel_format::Receipt, native_types: bool) -> Self {
         Self {
             receipt_index: r.receipt_index.into(),
-            root_contract_id: r.root_contract_id.map(|d| d.encode_hex()),
-            tx_id: r.tx_id.encode_hex(),
-            tx_status: r.tx_status.as_u8(),
-            tx_type: r.tx_type.to_u8(),
+            root_contract_id: r.root_contract_id.map(|v| Binary::new(v, native_types)),
+            tx_id: Binary::new(r.tx_id, native_types),
+            tx_status: r.tx_status.into(),
+            tx_type: r.tx_type.into(),
             block_height: r.block_height.into(),
             pc: r.pc.map(|d| d.into()),
             is: r.is.map(|d| d.into()),
-            to: r.to.map(|d| d.encode_hex()),
-            to_address: r.to_address.map(|d| d.encode_hex()),
+            to: r.to.map(|v| Binary::new(v, native_types)),
+            to_address: r.to_address.map(|v| Binary::new(v, native_types)),
             amount: r.amount.map(|d| d.into()),
-            asset_id: r.asset_id.map(|d| d.encode_hex()),
+            asset_id: r.asset_id.map(|v| Binary::new(v, native_types)),
             gas: r.gas.map(|d| d.into()),
             param1: r.param1.map(|d| d.into()),
             param2: r.param2.map(|d| d.into()),
             val: r.val.map(|d| d.into()),
             ptr: r.ptr.map(|d| d.into()),
-            digest: r.digest.map(|d| d.encode_hex()),
+            digest: r.digest.map(|v| Binary::new(v, native_types)),
             reason: r.reason.map(|d| d.into()),
             ra: r.ra.map(|d| d.into()),
             rb: r.rb.map(|d| d.into()),
             rc: r.rc.map(|d| d.into()),
             rd: r.rd.map(|d| d.into()),
             len: r.len.map(|d| d.into()),
-            receipt_type: r.receipt_type.to_u8(),
+            receipt_type: r.receipt_type.into(),
             result: r.result.map(|d| d.into()),
             gas_used: r.gas_used.map(|d| d.into()),
-            data: r.data.map(|d| d.encode_hex()),
-            sender: r.sender.map(|d| d.encode_hex()),
-            recipient: r.recipient.map(|d| d.encode_hex()),
-            nonce: r.nonce.map(|d| d.encode_hex()),
-            contract_id: r.contract_id.map(|d| d.encode_hex()),
-            sub_id: r.sub_id.map(|d| d.encode_hex()),
+            data: r.data.map(|v| Binary::new(v, native_types)),
+            sender: r.sender.map(|v| Binary::new(v, native_types)),
+            recipient: r.recipient.map(|v| Binary::new(v, native_types)),
+            nonce: r.nonce.map(|v| Binary::new(v, native_types)),
+            contract_id: r.contract_id.map(|v| Binary::new(v, native_types)),
+            sub_id: r.sub_id.map(|v| Binary::new(v, native_types)),
+            decoded_call: None,
         }
     }
 }
 
-impl From<hyperfuel_format::Input> for Input {
-    fn from(i: hyperfuel_format::Input) -> Self {
+impl Input {
+    pub fn new(i: hyperfuel_format::Input, native_types: bool) -> Self {
         Self {
-            tx_id: i.tx_id.encode_hex(),
-            tx_status: i.tx_status.as_u8(),
-            tx_type: i.tx_type.to_u8(),
+            tx_id: Binary::new(i.tx_id, native_types),
+            tx_status: i.tx_status.into(),
+            tx_type: i.tx_type.into(),
             block_height: i.block_height.into(),
-            input_type: i.input_type.as_u8(),
-            utxo_id: i.utxo_id.map(|d| d.encode_hex()),
-            owner: i.owner.map(|d| d.encode_hex()),
+            input_type: i.input_type.into(),
+            utxo_id: i.utxo_id.map(|v| Binary::new(v, native_types)),
+            owner: i.owner.map(|v| Binary::new(v, native_types)),
             amount: i.amount.map(|d| d.into()),
-            asset_id: i.asset_id.map(|d| d.encode_hex()),
+            asset_id: i.asset_id.map(|v| Binary::new(v, native_types)),
             tx_pointer_block_height: i.tx_pointer_block_height.map(|d| d.into()),
             tx_pointer_tx_index: i.tx_pointer_tx_index.map(|d| d.into()),
             witness_index: i.witness_index.map(|d| d.into()),
             predicate_gas_used: i.predicate_gas_used.map(|d| d.into()),
-            predicate: i.predicate.map(|d| d.encode_hex()),
-            predicate_data: i.predicate_data.map(|d| d.encode_hex()),
-            balance_root: i.balance_root.map(|d| d.encode_hex()),
-            state_root: i.state_root.map(|d| d.encode_hex()),
-            contract: i.contract.map(|d| d.encode_hex()),
-            sender: i.sender.map(|d| d.encode_hex()),
-            recipient: i.recipient.map(|d| d.encode_hex()),
-            nonce: i.nonce.map(|d| d.encode_hex()),
-            data: i.data.map(|d| d.encode_hex()),
+            predicate: i.predicate.map(|v| Binary::new(v, native_types)),
+            predicate_data: i.predicate_data.map(|v| Binary::new(v, native_types)),
+            balance_root: i.balance_root.map(|v| Binary::new(v, native_types)),
+            state_root: i.state_root.map(|v| Binary::new(v, native_types)),
+            contract: i.contract.map(|v| Binary::new(v, native_types)),
+            sender: i.sender.map(|v| Binary::new(v, native_types)),
+            recipient: i.recipient.map(|v| Binary::new(v, native_types)),
+            nonce: i.nonce.map(|v| Binary::new(v, native_types)),
+            data: i.data.map(|v| Binary::new(v, native_types)),
         }
     }
 }
 
-impl From<hyperfuel_format::Output> for Output {
-    fn from(o: hyperfuel_format::Output) -> Self {
+impl Output {
+    pub fn new(o: hyperfuel_format::Output, native_types: bool) -> Self {
         Self {
-            tx_id: o.tx_id.encode_hex(),
-            tx_status: o.tx_status.as_u8(),
-            tx_type: o.tx_type.to_u8(),
+            tx_id: Binary::new(o.tx_id, native_types),
+            tx_status: o.tx_status.into(),
+            tx_type: o.tx_type.into(),
             block_height: o.block_height.into(),
-            output_type: o.output_type.as_u8(),
-            to: o.to.map(|d| d.encode_hex()),
+            output_type: o.output_type.into(),
+            to: o.to.map(|v| Binary::new(v, native_types)),
             amount: o.amount.map(|d| d.into()),
-            asset_id: o.asset_id.map(|d| d.encode_hex()),
+            asset_id: o.asset_id.map(|v| Binary::new(v, native_types)),
             input_index: o.input_index.map(|d| d.into()),
-            balance_root: o.balance_root.map(|d| d.encode_hex()),
-            state_root: o.state_root.map(|d| d.encode_hex()),
-            contract: o.contract.map(|d| d.encode_hex()),
+            balance_root: o.balance_root.map(|v| Binary::new(v, native_types)),
+            state_root: o.state_root.map(|v| Binary::new(v, native_types)),
+            contract: o.contract.map(|v| Binary::new(v, native_types)),
         }
     }
 }